@@ -0,0 +1,60 @@
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use lru::LruCache;
+
+type Cache = LruCache<(u64, PathBuf), Vec<u8>>;
+
+/// A shared in-memory LRU cache of file contents for `Repository::cat_to_vec`
+///
+/// File contents at a given revision never change, so entries never need
+/// invalidating.  Capacity is a number of files, not bytes.  Clones share the
+/// same underlying cache.
+#[derive(Debug, Clone)]
+pub struct CatCache {
+    inner: Arc<Mutex<Cache>>,
+}
+
+fn capacity(capacity: usize) -> NonZeroUsize {
+    NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN)
+}
+
+impl CatCache {
+    /// Create a cache holding up to `capacity` files, minimum 1.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LruCache::new(self::capacity(capacity)))),
+        }
+    }
+
+    /// Change the number of files held, evicting the least recently used
+    /// entries if necessary.
+    pub fn resize(&self, capacity: usize) {
+        self.lock().resize(self::capacity(capacity));
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub(crate) fn get(&self, revision: u64, path: PathBuf) -> Option<Vec<u8>> {
+        self.lock().get(&(revision, path)).cloned()
+    }
+
+    pub(crate) fn put(&self, revision: u64, path: PathBuf, content: Vec<u8>) {
+        self.lock().put((revision, path), content);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
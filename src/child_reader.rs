@@ -1,7 +1,31 @@
 
+use std::fmt;
 use std::io::{self, Read};
 use std::process::{Child, ChildStdout, ExitStatus};
 
+/// The payload of the `io::Error` returned when a child exits unsuccessfully,
+/// so `SvnError`'s `From<io::Error>` can recover the status
+#[derive(Debug)]
+pub(crate) struct ExitError(pub(crate) ExitStatus);
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Subprocess exited with a non-zero return code: {}", self.0)
+    }
+}
+
+impl std::error::Error for ExitError {}
+
+impl ExitError {
+    pub(crate) fn check(status: ExitStatus) -> io::Result<()> {
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::Other, ExitError(status)))
+        }
+    }
+}
+
 /// A wrapper around a +Child+ which forwards +Read+ calls to its stdout, checks
 /// for a zero return code on EOF, and reaps the child on +Drop+.
 #[derive(Debug)]
@@ -17,6 +41,13 @@ impl ChildReader {
         self.child.wait()
     }
 
+    pub fn kill(&mut self) -> io::Result<ExitStatus> {
+        self.stdout = None;
+        self.child.kill()?;
+
+        self.child.wait()
+    }
+
     fn handle_io<F: FnOnce(&mut ChildStdout) -> io::Result<usize>>(
         &mut self,
         handler: F,
@@ -28,12 +59,7 @@ impl ChildReader {
             .unwrap_or(Err(io::Error::new(io::ErrorKind::BrokenPipe, "Pipe to subprocess closed")));
 
         if let Ok(0) = res {
-            if !self.finish()?.success() {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "Subprocess exited with a non-zero return code",
-                ))
-            }
+            ExitError::check(self.finish()?)?;
         }
 
         res
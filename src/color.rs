@@ -0,0 +1,88 @@
+use std::io::{self, BufRead, Read};
+
+const RED: &[u8] = b"\x1b[31m";
+const GREEN: &[u8] = b"\x1b[32m";
+const CYAN: &[u8] = b"\x1b[36m";
+const RESET: &[u8] = b"\x1b[0m";
+
+/// Wrap a diff stream, such as that returned by `SvnDiffBuilder::spawn`,
+/// inserting ANSI colour codes for hunk headers and added and removed lines.
+///
+/// File headers and property blocks are passed through uncoloured.  Input is
+/// processed a line at a time, so arbitrarily large diffs may be streamed.
+pub fn colorize_diff<R: BufRead>(reader: R) -> impl Read {
+    ColorizeDiff {
+        reader,
+        raw: vec![],
+        line: vec![],
+        pos: 0,
+        in_hunk: false,
+    }
+}
+
+#[derive(Debug)]
+struct ColorizeDiff<R> {
+    reader: R,
+    raw: Vec<u8>,
+    line: Vec<u8>,
+    pos: usize,
+    in_hunk: bool,
+}
+
+impl<R: BufRead> ColorizeDiff<R> {
+    fn color(&mut self) -> Option<&'static [u8]> {
+        match self.raw.first() {
+            Some(b'@') if self.raw.starts_with(b"@@") => {
+                self.in_hunk = true;
+                Some(CYAN)
+            }
+            Some(b'+') if self.in_hunk => Some(GREEN),
+            Some(b'-') if self.in_hunk => Some(RED),
+            Some(b' ') | Some(b'\\') if self.in_hunk => None,
+            _ => {
+                self.in_hunk = false;
+                None
+            }
+        }
+    }
+
+    fn fill_line(&mut self) -> io::Result<usize> {
+        self.raw.clear();
+        self.line.clear();
+        self.pos = 0;
+
+        if self.reader.read_until(b'\n', &mut self.raw)? == 0 {
+            return Ok(0);
+        }
+
+        match self.color() {
+            Some(color) => {
+                let (content, eol) = if self.raw.ends_with(b"\n") {
+                    self.raw.split_at(self.raw.len() - 1)
+                } else {
+                    (&self.raw[..], &b""[..])
+                };
+
+                self.line.extend_from_slice(color);
+                self.line.extend_from_slice(content);
+                self.line.extend_from_slice(RESET);
+                self.line.extend_from_slice(eol);
+            }
+            None => self.line.extend_from_slice(&self.raw),
+        }
+
+        Ok(self.line.len())
+    }
+}
+
+impl<R: BufRead> Read for ColorizeDiff<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.line.len() && self.fill_line()? == 0 {
+            return Ok(0);
+        }
+
+        let n = (&self.line[self.pos..]).read(buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
@@ -1,7 +1,72 @@
+pub(crate) mod cat;
+pub(crate) mod change_tree;
 pub(crate) mod changed;
 pub(crate) mod diff;
+pub(crate) mod diffstat;
+pub(crate) mod externals;
+pub(crate) mod filediff;
+#[cfg(feature = "fingerprint")]
+pub(crate) mod fingerprint;
+pub(crate) mod history;
 pub(crate) mod info;
+pub(crate) mod keywords;
+pub(crate) mod mergeinfo;
+pub(crate) mod tree;
+pub(crate) mod walk;
+pub(crate) mod word_diff;
 
+pub use cat::*;
+pub use change_tree::*;
 pub use changed::*;
 pub use diff::*;
+pub use diffstat::*;
+pub use externals::*;
+pub use filediff::*;
+#[cfg(feature = "fingerprint")]
+pub use fingerprint::*;
+pub(crate) use history::parse_history;
+pub use history::*;
 pub use info::*;
+pub(crate) use keywords::KeywordValues;
+pub use mergeinfo::*;
+pub use tree::*;
+pub use walk::*;
+pub use word_diff::*;
+
+use crate::SvnError;
+
+/// Remove a trailing `\r`, as left by splitting CRLF output on `\n`.
+fn strip_cr(slice: &[u8]) -> &[u8] {
+    slice.strip_suffix(b"\r").unwrap_or(slice)
+}
+
+/// Collect a streaming iterator into a `Vec`, stopping at the first error.
+/// On success svnlook's exit status has been checked by the iterator; on
+/// error `kill` is called rather than leaving svnlook to be reaped on drop.
+fn collect_or_kill<I, T>(mut iter: I, kill: fn(&mut I)) -> Result<Vec<T>, SvnError>
+where
+    I: Iterator<Item = Result<T, SvnError>>,
+{
+    let mut items = vec![];
+
+    while let Some(item) = iter.next() {
+        match item {
+            Ok(item) => items.push(item),
+            Err(e) => {
+                kill(&mut iter);
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+/// Remove the `\n` or `\r\n` terminating a line, failing if there is none.
+fn try_chomp(slice: &[u8]) -> Result<&[u8], SvnError> {
+    if slice.ends_with(b"\n") {
+        Ok(strip_cr(&slice[..slice.len() - 1]))
+    } else {
+        Err(SvnError::ParseError)
+    }
+}
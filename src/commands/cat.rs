@@ -0,0 +1,198 @@
+use std::io::{self, BufRead, Read};
+use std::process::{Child, Command, Stdio};
+use std::str;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::child_reader::ExitError;
+use crate::SvnError;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How many bytes of a file are inspected by `Repository::content_kind`
+pub(crate) const SNIFF_SIZE: u64 = 8 * 1024;
+
+/// Whether a file is suitable for display as text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ContentKind {
+    Text,
+    Binary,
+}
+
+impl ContentKind {
+    /// Classify an `svn:mime-type` value the way Subversion does, treating
+    /// anything other than `text/*` as binary.
+    pub fn from_mime_type(mime: &[u8]) -> Self {
+        if mime.starts_with(b"text/") {
+            ContentKind::Text
+        } else {
+            ContentKind::Binary
+        }
+    }
+
+    /// Classify a prefix of a file's contents.  NUL bytes or invalid UTF-8
+    /// mark it as binary; a multi-byte character cut off by the end of the
+    /// prefix does not.
+    pub fn sniff(prefix: &[u8]) -> Self {
+        if prefix.contains(&0) {
+            return ContentKind::Binary;
+        }
+
+        match str::from_utf8(prefix) {
+            Err(e) if e.error_len().is_some() => ContentKind::Binary,
+            _ => ContentKind::Text,
+        }
+    }
+}
+
+/// Compare two streams byte for byte, stopping at the first difference.
+pub(crate) fn same_content<A: BufRead, B: BufRead>(mut a: A, mut b: B) -> io::Result<bool> {
+    loop {
+        let (len, equal) = {
+            let (x, y) = (a.fill_buf()?, b.fill_buf()?);
+            if x.is_empty() || y.is_empty() {
+                return Ok(x.is_empty() && y.is_empty());
+            }
+
+            let len = x.len().min(y.len());
+            (len, x[..len] == y[..len])
+        };
+
+        if !equal {
+            return Ok(false);
+        }
+
+        a.consume(len);
+        b.consume(len);
+    }
+}
+
+/// A reader over `svnlook cat` output which fails with `ErrorKind::TimedOut`
+/// if no bytes arrive within the idle window, or if the optional overall
+/// deadline passes.  The child is killed when either timeout fires.
+#[derive(Debug)]
+pub struct SvnCatReader {
+    child: Child,
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+    idle: Duration,
+    deadline: Option<Instant>,
+    finished: bool,
+}
+
+impl SvnCatReader {
+    pub(crate) fn spawn(
+        cmd: &mut Command,
+        idle: Duration,
+        total: Option<Duration>,
+    ) -> Result<Self, SvnError> {
+        let mut child = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let (tx, chunks) = mpsc::sync_channel(1);
+
+        // Blocking reads happen on a helper thread so each read from the
+        // consumer can wait with its own deadline.
+        thread::spawn(move || loop {
+            let mut buf = vec![0; CHUNK_SIZE];
+            match stdout.read(&mut buf) {
+                Ok(n) => {
+                    buf.truncate(n);
+                    if tx.send(Ok(buf)).is_err() || n == 0 {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            chunks,
+            chunk: vec![],
+            pos: 0,
+            idle,
+            deadline: total.map(|total| Instant::now() + total),
+            finished: false,
+        })
+    }
+
+    fn kill(&mut self) {
+        self.finished = true;
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    fn next_chunk(&mut self) -> io::Result<Vec<u8>> {
+        let wait = match self.deadline {
+            Some(deadline) => self
+                .idle
+                .min(deadline.saturating_duration_since(Instant::now())),
+            None => self.idle,
+        };
+
+        match self.chunks.recv_timeout(wait) {
+            Ok(Ok(chunk)) => Ok(chunk),
+            Ok(Err(e)) => {
+                self.kill();
+                Err(e)
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                self.kill();
+                let reason = if self.deadline.is_some_and(|d| Instant::now() >= d) {
+                    "svnlook cat exceeded its total timeout"
+                } else {
+                    "svnlook cat produced no output within its idle timeout"
+                };
+                Err(io::Error::new(io::ErrorKind::TimedOut, reason))
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                self.kill();
+                Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "Pipe to subprocess closed",
+                ))
+            }
+        }
+    }
+}
+
+impl Read for SvnCatReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.chunk.len() {
+            if self.finished {
+                return Ok(0);
+            }
+
+            self.chunk = self.next_chunk()?;
+            self.pos = 0;
+
+            if self.chunk.is_empty() {
+                self.finished = true;
+                ExitError::check(self.child.wait()?)?;
+                return Ok(0);
+            }
+        }
+
+        let n = (&self.chunk[self.pos..]).read(buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Drop for SvnCatReader {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.kill();
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+use crate::{SvnChange, SvnError};
+
+/// Changes assembled into a nested directory tree, as returned by
+/// `SvnChangedIter::into_tree`
+///
+/// Each node corresponds to a path component.  Nodes exist for every
+/// directory leading to a change, whether or not the directory itself was
+/// changed.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChangeTree {
+    /// Child nodes, keyed by name
+    pub children: BTreeMap<String, ChangeTree>,
+    /// The change to this node itself, if any
+    pub change: Option<SvnChange>,
+    /// The number of changes at or beneath this node
+    pub count: usize,
+}
+
+fn components(path: &Path) -> impl Iterator<Item = String> + '_ {
+    path.components().filter_map(|c| match c {
+        Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+        _ => None,
+    })
+}
+
+impl ChangeTree {
+    /// Build a tree from a stream of changes, stopping at the first error.
+    pub fn from_changes<I>(changes: I) -> Result<Self, SvnError>
+    where
+        I: IntoIterator<Item = Result<SvnChange, SvnError>>,
+    {
+        let mut tree = Self::default();
+
+        for change in changes {
+            tree.insert(change?);
+        }
+
+        Ok(tree)
+    }
+
+    /// Add a change, creating intermediate directory nodes as needed.
+    pub fn insert(&mut self, change: SvnChange) {
+        let mut node = self;
+        node.count += 1;
+
+        for name in components(&change.path) {
+            node = node.children.entry(name).or_default();
+            node.count += 1;
+        }
+
+        node.change = Some(change);
+    }
+
+    /// Look up the node for a path relative to the root of the tree.
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&ChangeTree> {
+        components(path.as_ref()).try_fold(self, |node, name| node.children.get(&name))
+    }
+}
@@ -1,30 +1,72 @@
+use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
-use std::io::BufRead;
-use std::path::PathBuf;
+use std::io::{BufRead, Write};
+use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use crate::{SvnError, SvnlookCommand};
+use super::{collect_or_kill, try_chomp};
+#[cfg(feature = "glob")]
+use crate::PathPolicy;
+use crate::{ChangeTree, Repository, Revision, SvnError, SvnlookCommand};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SvnStatus {
     Added,
     Copied(SvnFrom),
     Deleted,
     Updated,
     PropChange,
+    /// Deleted and re-added in the same revision, optionally from a copy
+    Replaced(Option<SvnFrom>),
 }
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SvnFrom {
     pub path: PathBuf,
     pub revision: u64,
+    /// Set if the copy source is a directory, as marked by a trailing slash
+    pub from_is_dir: bool,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SvnChange {
     pub path: PathBuf,
     pub status: SvnStatus,
+    /// Set if the path is a directory, as marked by a trailing slash
+    pub is_dir: bool,
+    /// Set if properties changed, as marked by a `U` in the second column.
+    /// This is true for `PropChange`, and for `Updated` where both contents
+    /// and properties changed.
+    pub props_changed: bool,
+    /// `Some(true)` if `svn:executable` was set by this change, `Some(false)`
+    /// if it was removed, and `None` if unchanged or not inspected.  Only
+    /// populated by `Repository::changed_with_exec`.
+    pub exec_changed: Option<bool>,
+}
+
+/// Whether a renamed path also changed, as classified by `Repository::renames`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum RenameKind {
+    /// Moved without modification
+    Pure,
+    /// Moved and edited in the same revision
+    Modified,
+}
+
+/// A copy whose source was deleted in the same revision
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Rename {
+    pub from: SvnFrom,
+    pub to: PathBuf,
+    pub is_dir: bool,
+    pub kind: RenameKind,
 }
 
 impl fmt::Display for SvnStatus {
@@ -38,16 +80,55 @@ impl fmt::Display for SvnStatus {
                 SvnStatus::Deleted => "Deleted",
                 SvnStatus::Updated => "Updated",
                 SvnStatus::PropChange => "PropChange",
+                SvnStatus::Replaced(_) => "Replaced",
             }
         )
     }
 }
 
-fn try_chomp(slice: &[u8]) -> Result<&[u8], SvnError> {
-    if slice.ends_with(b"\n") {
-        Ok(&slice[..slice.len() - 1])
-    } else {
-        Err(SvnError::ParseError)
+impl SvnStatus {
+    /// A short lowercase label of at most four characters, for fixed-width
+    /// columns.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SvnStatus::Added => "add",
+            SvnStatus::Copied(_) => "copy",
+            SvnStatus::Deleted => "del",
+            SvnStatus::Updated => "mod",
+            SvnStatus::PropChange => "prop",
+            SvnStatus::Replaced(_) => "repl",
+        }
+    }
+
+    /// Position in the order used by `SvnChangedIter::ordered_for_review`
+    fn review_rank(&self) -> u8 {
+        match self {
+            SvnStatus::Added => 0,
+            SvnStatus::Copied(_) => 1,
+            SvnStatus::Replaced(_) => 2,
+            SvnStatus::Updated => 3,
+            SvnStatus::PropChange => 4,
+            SvnStatus::Deleted => 5,
+        }
+    }
+}
+
+impl SvnChange {
+    /// A short label for the change's status, such as `"add"` or `"prop"`.
+    pub fn display_label(&self) -> &'static str {
+        self.status.label()
+    }
+
+    /// The status label left-aligned and padded to `width` columns.
+    pub fn padded_label(&self, width: usize) -> String {
+        format!("{:<width$}", self.display_label(), width = width)
+    }
+}
+
+fn strip_dir_slash(path: &[u8]) -> (&[u8], bool) {
+    match path.strip_suffix(b"/") {
+        Some(stripped) if !stripped.is_empty() => (stripped, true),
+        _ => (path, path == b"/"),
     }
 }
 
@@ -66,6 +147,8 @@ impl TryFrom<&[u8]> for SvnStatus {
             b"U  " => SvnStatus::Updated,
             b"_U " => SvnStatus::PropChange,
             b"UU " => SvnStatus::Updated,
+            b"R  " => SvnStatus::Replaced(None),
+            b"R +" => SvnStatus::Replaced(Some(SvnFrom::default())),
             _ => return Err(SvnError::ParseError),
         })
     }
@@ -82,9 +165,13 @@ impl TryFrom<&[u8]> for SvnChange {
         }
 
         let (change, path) = line.split_at(4);
+        let (path, is_dir) = strip_dir_slash(path);
         Ok(SvnChange {
             path: PathBuf::from(String::from_utf8_lossy(path).to_string()),
             status: change.try_into()?,
+            is_dir,
+            props_changed: change[1] == b'U',
+            exec_changed: None,
         })
     }
 }
@@ -109,19 +196,37 @@ impl TryFrom<&[u8]> for SvnFrom {
                 std::str::from_utf8(&revision[2..])
                     .map_err(SvnError::from)
                     .and_then(|s| u64::from_str(s).map_err(SvnError::from))
-                    .map(|revision| SvnFrom {
-                        path: PathBuf::from(String::from_utf8_lossy(path).to_string()),
-                        revision,
+                    .map(|revision| {
+                        let (path, from_is_dir) = strip_dir_slash(path);
+                        SvnFrom {
+                            path: PathBuf::from(String::from_utf8_lossy(path).to_string()),
+                            revision,
+                            from_is_dir,
+                        }
                     })
             })
     }
 }
 
+/// A streaming iterator over the paths changed in a revision, as returned by
+/// `Repository::changed`
+///
+/// Output is parsed a line at a time.  svnlook does not escape paths, but
+/// Subversion refuses to commit paths containing control characters such as
+/// newlines, so a path cannot span lines.  Should one appear anyway, from a
+/// repository created by other means, any line which is not a well-formed
+/// change yields `SvnError::MalformedChange` rather than being skipped or
+/// split into another change.
 #[derive(Debug)]
 pub struct SvnChangedIter {
     svnlook: SvnlookCommand,
     line: Vec<u8>,
     finished: bool,
+    exec: Option<(Repository, Revision)>,
+    copies: CopyOrigins,
+    include: Vec<PathBuf>,
+    exclude: Vec<PathBuf>,
+    base: Option<PathBuf>,
 }
 
 impl From<SvnlookCommand> for SvnChangedIter {
@@ -130,6 +235,11 @@ impl From<SvnlookCommand> for SvnChangedIter {
             svnlook: cmd,
             line: vec![],
             finished: false,
+            exec: None,
+            copies: CopyOrigins::default(),
+            include: vec![],
+            exclude: vec![],
+            base: None,
         }
     }
 }
@@ -140,17 +250,310 @@ impl Drop for SvnChangedIter {
     }
 }
 
+fn relative(path: PathBuf) -> PathBuf {
+    match path.strip_prefix("/") {
+        Ok(path) => path.to_path_buf(),
+        Err(_) => path,
+    }
+}
+
+fn has_executable(repository: &Repository, revision: u64, path: &Path) -> Result<bool, SvnError> {
+    Ok(repository
+        .optional_prop(revision, "svn:executable", path)?
+        .is_some())
+}
+
+/// Copy destinations within a revision, used to find where a path came from
+/// when it or one of its ancestors was copied
+#[derive(Debug, Default)]
+pub(crate) struct CopyOrigins(BTreeMap<PathBuf, SvnFrom>);
+
+impl CopyOrigins {
+    pub(crate) fn insert(&mut self, change: &SvnChange) {
+        if let SvnStatus::Copied(from) | SvnStatus::Replaced(Some(from)) = &change.status {
+            self.0.insert(change.path.clone(), from.clone());
+        }
+    }
+
+    /// The copy source of `path` or of its nearest copied ancestor, with the
+    /// remainder of the path appended.
+    pub(crate) fn origin(&self, path: &Path, is_dir: bool) -> Option<SvnFrom> {
+        path.ancestors().find_map(|dest| {
+            let from = self.0.get(dest)?;
+            let rest = path.strip_prefix(dest).ok()?;
+
+            Some(if rest.as_os_str().is_empty() {
+                from.clone()
+            } else {
+                SvnFrom {
+                    path: from.path.join(rest),
+                    revision: from.revision,
+                    from_is_dir: is_dir,
+                }
+            })
+        })
+    }
+}
+
+impl<'a> FromIterator<&'a SvnChange> for CopyOrigins {
+    fn from_iter<I: IntoIterator<Item = &'a SvnChange>>(changes: I) -> Self {
+        let mut origins = CopyOrigins::default();
+        changes
+            .into_iter()
+            .for_each(|change| origins.insert(change));
+        origins
+    }
+}
+
+fn exec_changed(
+    repository: &Repository,
+    revision: Revision,
+    change: &SvnChange,
+    origin: Option<SvnFrom>,
+) -> Result<Option<bool>, SvnError> {
+    let before = match (&change.status, origin) {
+        (SvnStatus::Added, _) | (SvnStatus::Replaced(None), _) => false,
+        (SvnStatus::Deleted, _) => return Ok(None),
+        (_, Some(from)) => has_executable(repository, from.revision, &from.path)?,
+        (_, None) => revision.0 > 0 && has_executable(repository, revision.0 - 1, &change.path)?,
+    };
+
+    let after = has_executable(repository, revision.0, &change.path)?;
+
+    Ok(if before != after { Some(after) } else { None })
+}
+
 impl SvnChangedIter {
-    fn parse(&mut self) -> Result<SvnChange, SvnError> {
-        let mut change = SvnChange::try_from(&self.line[..])?;
+    pub(crate) fn kill(&mut self) {
+        self.finished = true;
+        let _ = self.svnlook.kill();
+    }
+
+    pub(crate) fn with_exec(mut self, repository: Repository, revision: Revision) -> Self {
+        self.exec = Some((repository, revision));
+        self
+    }
+
+    /// Only yield changes at or beneath `prefix`.  May be called repeatedly
+    /// to include several prefixes.
+    pub fn under<P: Into<PathBuf>>(mut self, prefix: P) -> Self {
+        self.include.push(relative(prefix.into()));
+        self
+    }
+
+    /// Skip changes at or beneath any of `prefixes`, such as generated or
+    /// vendored directories.  Exclusions take precedence over `under`.
+    pub fn excluding(mut self, prefixes: Vec<PathBuf>) -> Self {
+        self.exclude.extend(prefixes.into_iter().map(relative));
+        self
+    }
+
+    /// Report paths relative to `base`, skipping changes outside it.  Copy
+    /// sources within `base` are made relative too; those outside it are
+    /// given a leading `/`, relative to the repository root, so the two can
+    /// be told apart.  A change to `base` itself has an empty path.
+    ///
+    /// `under` and `excluding` still match against full paths.
+    pub fn strip_prefix<P: Into<PathBuf>>(mut self, base: P) -> Self {
+        self.base = Some(relative(base.into()));
+        self
+    }
+
+    fn wanted(&self, path: &Path) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| path.starts_with(p)))
+            && !self.exclude.iter().any(|p| path.starts_with(p))
+            && self
+                .base
+                .as_ref()
+                .map_or(true, |base| path.starts_with(base))
+    }
+
+    fn rebase(&self, change: &mut SvnChange) {
+        let base = match &self.base {
+            Some(base) => base,
+            None => return,
+        };
+
+        if let Ok(path) = change.path.strip_prefix(base) {
+            change.path = path.to_path_buf();
+        }
+
+        if let SvnStatus::Copied(from) | SvnStatus::Replaced(Some(from)) = &mut change.status {
+            let source = relative(from.path.clone());
+            from.path = match source.strip_prefix(base) {
+                Ok(path) => path.to_path_buf(),
+                Err(_) => Path::new("/").join(source),
+            };
+        }
+    }
+
+    fn malformed(&self) -> SvnError {
+        SvnError::MalformedChange(String::from_utf8_lossy(&self.line).trim_end().to_string())
+    }
+
+    fn parse_from(&mut self) -> Result<SvnFrom, SvnError> {
         self.line.clear();
+        self.svnlook.read_until(b'\n', &mut self.line)?;
+        SvnFrom::try_from(&self.line[..]).map_err(|_| self.malformed())
+    }
+
+    fn parse(&mut self) -> Result<Option<SvnChange>, SvnError> {
+        let mut change = SvnChange::try_from(&self.line[..]).map_err(|_| self.malformed())?;
+
+        match change.status {
+            SvnStatus::Copied(_) => change.status = SvnStatus::Copied(self.parse_from()?),
+            SvnStatus::Replaced(Some(_)) => {
+                change.status = SvnStatus::Replaced(Some(self.parse_from()?))
+            }
+            _ => (),
+        }
+        self.line.clear();
+
+        // svnlook lists paths in sorted order, so any copied ancestor has
+        // already been seen, even if it is filtered out below
+        if self.exec.is_some() {
+            self.copies.insert(&change);
+        }
 
-        if let SvnStatus::Copied(_) = change.status {
-            self.svnlook.read_until(b'\n', &mut self.line)?;
-            change.status = SvnStatus::Copied(SvnFrom::try_from(&self.line[..])?);
+        if !self.wanted(&change.path) {
+            return Ok(None);
         }
 
-        Ok(change)
+        if let Some((repository, revision)) = &self.exec {
+            let may_have_changed = match change.status {
+                SvnStatus::Added | SvnStatus::Copied(_) | SvnStatus::Replaced(_) => true,
+                _ => change.props_changed,
+            };
+
+            if !change.is_dir && may_have_changed {
+                let origin = self.copies.origin(&change.path, false);
+                change.exec_changed = exec_changed(repository, *revision, &change, origin)?;
+            }
+        }
+
+        self.rebase(&mut change);
+        Ok(Some(change))
+    }
+
+    /// Write each remaining changed path followed by a NUL byte, as expected
+    /// by `xargs -0`.
+    pub fn write_nul_separated<W: Write>(&mut self, w: &mut W) -> Result<(), SvnError> {
+        for change in self {
+            w.write_all(change?.path.to_string_lossy().as_bytes())?;
+            w.write_all(b"\0")?;
+        }
+
+        Ok(())
+    }
+
+    /// Consume the iterator, bucketing changes by their immediate parent
+    /// directory.  Changes within each directory retain svnlook's ordering,
+    /// and the first error encountered is returned.
+    pub fn group_by_dir(self) -> Result<BTreeMap<PathBuf, Vec<SvnChange>>, SvnError> {
+        let mut groups: BTreeMap<PathBuf, Vec<SvnChange>> = BTreeMap::new();
+
+        for change in self {
+            let change = change?;
+            let dir = change
+                .path
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .to_path_buf();
+
+            groups.entry(dir).or_default().push(change);
+        }
+
+        Ok(groups)
+    }
+
+    /// Only yield changes to directories.
+    pub fn dirs_only(self) -> impl Iterator<Item = Result<SvnChange, SvnError>> {
+        self.filter(|change| change.as_ref().map_or(true, |change| change.is_dir))
+    }
+
+    /// Only yield changes to files.
+    pub fn files_only(self) -> impl Iterator<Item = Result<SvnChange, SvnError>> {
+        self.filter(|change| change.as_ref().map_or(true, |change| !change.is_dir))
+    }
+
+    /// Yield a separate entry for each aspect of a change, so an entry with
+    /// both contents and properties changed becomes an `Updated` entry
+    /// followed by a `PropChange` entry.  Other entries pass through as-is.
+    pub fn split_aspects(self) -> impl Iterator<Item = Result<SvnChange, SvnError>> {
+        self.flat_map(|change| match change {
+            Ok(change) if change.status == SvnStatus::Updated && change.props_changed => vec![
+                Ok(SvnChange {
+                    props_changed: false,
+                    ..change.clone()
+                }),
+                Ok(SvnChange {
+                    status: SvnStatus::PropChange,
+                    ..change
+                }),
+            ],
+            change => vec![change],
+        })
+    }
+
+    /// Only yield changes which add or modify a path `policy` forbids.
+    /// Deletions never violate a policy, so removing a forbidden file is
+    /// always allowed.
+    #[cfg(feature = "glob")]
+    pub fn violations(
+        self,
+        policy: &PathPolicy,
+    ) -> impl Iterator<Item = Result<SvnChange, SvnError>> + '_ {
+        self.filter(move |change| {
+            change.as_ref().map_or(true, |change| {
+                change.status != SvnStatus::Deleted
+                    && policy.forbids(&change.path, change.is_dir).is_some()
+            })
+        })
+    }
+
+    /// Consume the iterator, pairing each change with its logical origin: the
+    /// copy source of the change itself or of its nearest copied ancestor,
+    /// with the remainder of the path appended.  For a revision copying
+    /// `trunk` to `branches/x` and modifying `branches/x/f`, the origin of
+    /// the modification is `trunk/f` at the copy source revision.
+    ///
+    /// Changes not beneath any copy have no origin, and neither do paths added
+    /// or replaced without history beneath one, as their content is new.
+    pub fn with_copy_origins(self) -> Result<Vec<(SvnChange, Option<SvnFrom>)>, SvnError> {
+        let changes = self.collect_changes()?;
+        let copies: CopyOrigins = changes.iter().collect();
+
+        let origins = changes
+            .iter()
+            .map(|change| match change.status {
+                SvnStatus::Added | SvnStatus::Replaced(None) => None,
+                _ => copies.origin(&change.path, change.is_dir),
+            })
+            .collect::<Vec<_>>();
+
+        Ok(changes.into_iter().zip(origins).collect())
+    }
+
+    /// Consume the iterator, assembling changes into a nested directory tree
+    /// with per-node change counts.
+    pub fn into_tree(self) -> Result<ChangeTree, SvnError> {
+        ChangeTree::from_changes(self)
+    }
+
+    /// Consume the iterator, sorting changes for presentation in a review:
+    /// additions, then copies, replacements, modifications, property-only
+    /// changes and finally deletions, each sorted by path.
+    pub fn ordered_for_review(self) -> Result<Vec<SvnChange>, SvnError> {
+        let mut changes = self.collect_changes()?;
+        changes.sort_by(|a, b| {
+            (a.status.review_rank(), &a.path).cmp(&(b.status.review_rank(), &b.path))
+        });
+        Ok(changes)
+    }
+
+    /// Consume the iterator into a `Vec`, stopping at the first error.
+    pub fn collect_changes(self) -> Result<Vec<SvnChange>, SvnError> {
+        collect_or_kill(self, Self::kill)
     }
 }
 
@@ -158,23 +561,183 @@ impl Iterator for SvnChangedIter {
     type Item = Result<SvnChange, SvnError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.line.clear();
+        loop {
+            self.line.clear();
 
-        if self.finished {
-            return None;
-        }
+            if self.finished {
+                return None;
+            }
 
-        match self.svnlook.read_until(b'\n', &mut self.line) {
-            Ok(0) => {
-                self.finished = true;
-                match self.svnlook.finish() {
-                    Ok(status) if status.success() => None,
-                    Ok(status) => Some(Err(SvnError::ExitFailure(status))),
-                    Err(e) => Some(Err(e)),
+            match self.svnlook.read_until(b'\n', &mut self.line) {
+                Ok(0) => {
+                    self.finished = true;
+                    return match self.svnlook.finish() {
+                        Ok(status) if status.success() => None,
+                        Ok(status) => Some(Err(SvnError::from(status))),
+                        Err(e) => Some(Err(e)),
+                    };
                 }
+                Ok(_) => match self.parse() {
+                    Ok(Some(change)) => return Some(Ok(change)),
+                    Ok(None) => continue,
+                    Err(e) => return Some(Err(e)),
+                },
+                Err(e) => return Some(Err(SvnError::from(e))),
             }
-            Ok(_) => Some(self.parse()),
-            Err(e) => Some(Err(SvnError::from(e))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaced_without_copy() {
+        let change = SvnChange::try_from(&b"R   trunk/a\n"[..]).unwrap();
+        assert_eq!(change.status, SvnStatus::Replaced(None));
+        assert_eq!(change.path, Path::new("trunk/a"));
+    }
+
+    #[test]
+    fn replaced_with_copy() {
+        let change = SvnChange::try_from(&b"R + trunk/a\n"[..]).unwrap();
+        assert_eq!(change.status, SvnStatus::Replaced(Some(SvnFrom::default())));
+
+        let from = SvnFrom::try_from(&b"    (from branches/x/a:r3)\n"[..]).unwrap();
+        assert_eq!(from.path, Path::new("branches/x/a"));
+        assert_eq!(from.revision, 3);
+        assert!(!from.from_is_dir);
+    }
+
+    #[test]
+    fn backslashes_are_verbatim() {
+        let change = SvnChange::try_from(&b"A   trunk/a\\012b\\x\n"[..]).unwrap();
+        assert_eq!(change.path, Path::new("trunk/a\\012b\\x"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn split_path_is_an_error() {
+        let repo = crate::testing::fake_repository("printf 'A   trunk/a\\nb\\n'\n");
+
+        match repo.changed(3).unwrap().collect_changes() {
+            Err(SvnError::MalformedChange(line)) => assert_eq!(line, "b"),
+            other => panic!("expected MalformedChange, got {:?}", other),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_origins_skip_new_content() {
+        let repo = crate::testing::fake_repository(
+            "printf 'A + branches/x/\\n    (from trunk/:r4)\\nA   branches/x/new\\nR   branches/x/repl\\nU   branches/x/f\\n'\n",
+        );
+
+        let origins = repo
+            .changed(5)
+            .unwrap()
+            .with_copy_origins()
+            .unwrap()
+            .into_iter()
+            .map(|(change, from)| (change.path, from.map(|from| (from.path, from.revision))))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            origins,
+            [
+                (
+                    PathBuf::from("branches/x"),
+                    Some((PathBuf::from("trunk"), 4))
+                ),
+                (PathBuf::from("branches/x/new"), None),
+                (PathBuf::from("branches/x/repl"), None),
+                (
+                    PathBuf::from("branches/x/f"),
+                    Some((PathBuf::from("trunk/f"), 4))
+                ),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn strip_prefix_copy_sources() {
+        let repo = crate::testing::fake_repository(
+            "printf 'U   trunk/p/\\nA + trunk/p/x\\n    (from trunk/p/lib/a:r4)\\nA + trunk/p/y\\n    (from lib/a:r4)\\nA   other/z\\n'\n",
+        );
+
+        let changes = repo
+            .changed(5)
+            .unwrap()
+            .strip_prefix("/trunk/p")
+            .map(|change| {
+                let change = change.unwrap();
+                let from = match change.status {
+                    SvnStatus::Copied(from) => Some(from.path),
+                    _ => None,
+                };
+                (change.path, from)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            [
+                (PathBuf::new(), None),
+                (PathBuf::from("x"), Some(PathBuf::from("lib/a"))),
+                (PathBuf::from("y"), Some(PathBuf::from("/lib/a"))),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replaced_state_machine() {
+        let repo = crate::testing::fake_repository(
+            "printf 'R   a\\nR + b\\n    (from c:r2)\\nD   c\\nA   d\\n'\n",
+        );
+        let changes = repo.changed(3).unwrap().collect_changes().unwrap();
+
+        let statuses = changes
+            .iter()
+            .map(|change| (change.path.to_str().unwrap(), change.status.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                ("a", SvnStatus::Replaced(None)),
+                (
+                    "b",
+                    SvnStatus::Replaced(Some(SvnFrom {
+                        path: PathBuf::from("c"),
+                        revision: 2,
+                        from_is_dir: false,
+                    }))
+                ),
+                ("c", SvnStatus::Deleted),
+                ("d", SvnStatus::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn crlf_matches_lf() {
+        for (lf, crlf) in &[
+            (&b"A   trunk/a\n"[..], &b"A   trunk/a\r\n"[..]),
+            (b"_U  trunk/dir/\n", b"_U  trunk/dir/\r\n"),
+            (b"A + trunk/b\n", b"A + trunk/b\r\n"),
+        ] {
+            // SvnChange has no PartialEq, so compare every field via Debug
+            assert_eq!(
+                format!("{:?}", SvnChange::try_from(*crlf).unwrap()),
+                format!("{:?}", SvnChange::try_from(*lf).unwrap())
+            );
+        }
+
+        assert_eq!(
+            SvnFrom::try_from(&b"    (from trunk/c/:r2)\r\n"[..]).unwrap(),
+            SvnFrom::try_from(&b"    (from trunk/c/:r2)\n"[..]).unwrap()
+        );
+    }
+}
@@ -1,21 +1,33 @@
+use std::io::{self, BufRead, Read};
 use std::process::Command;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::{SvnError, SvnlookCommand};
+use crate::{Revision, SvnDiffIter, SvnError, SvnlookCommand};
 
+/// A builder for `svnlook diff`, as returned by `Repository::diff`
+///
+/// Options are passed in the order they are set, followed by a single `-x`
+/// argument combining any extensions, since svnlook only honours the last
+/// `-x` given.  All precede the `--` before the repository path.
 #[derive(Debug)]
 pub struct SvnDiffBuilder {
     repository: PathBuf,
     command: Command,
+    extensions: Vec<String>,
+    #[cfg(feature = "glob")]
+    ignore: Option<globset::GlobSet>,
 }
 
 impl SvnDiffBuilder {
-    pub(crate) fn new(repository: &PathBuf, mut command: Command) -> Self {
+    pub(crate) fn new(repository: &Path, mut command: Command) -> Self {
         command.arg("diff");
 
         Self {
-            repository: repository.clone(),
-            command
+            repository: repository.to_path_buf(),
+            command,
+            extensions: vec![],
+            #[cfg(feature = "glob")]
+            ignore: None,
         }
     }
 
@@ -39,50 +51,254 @@ impl SvnDiffBuilder {
         self
     }
 
+    /// Only diff properties.  With `spawn_structured`, this yields file diffs
+    /// with empty `hunks` and only `properties` populated.
     pub fn properties_only(&mut self) -> &mut Self {
         self.command.arg("--properties-only");
         self
     }
 
     pub fn ignore_whitespace_change(&mut self) -> &mut Self {
-        self.command.args(&["-x", "-b"]);
+        self.extensions.push("-b".to_string());
         self
     }
 
     pub fn ignore_all_whitespace(&mut self) -> &mut Self {
-        self.command.args(&["-x", "-w"]);
+        self.extensions.push("-w".to_string());
         self
     }
 
     pub fn ignore_eol_style(&mut self) -> &mut Self {
-        self.command.args(&["-x", "--ignore-eof-style"]);
+        self.extensions.push("--ignore-eol-style".to_string());
         self
     }
 
     pub fn show_c_function_name(&mut self) -> &mut Self {
-        self.command.args(&["-x", "-p"]);
+        self.extensions.push("-p".to_string());
         self
     }
 
     pub fn show_c_function_names(&mut self) -> &mut Self {
-        self.command.args(&["-x", "-p"]);
+        self.extensions.push("-p".to_string());
         self
     }
 
     pub fn context_lines(&mut self, lines: u32) -> &mut Self {
-        self.command.arg("-x");
-        self.command.arg(format!("-U{}", lines));
+        self.extensions.push(format!("-U{}", lines));
         self
     }
 
-    pub fn revision(&mut self, revision: u64) -> &mut Self {
-        self.command.arg(format!("-r{}", revision));
+    /// Drop files matching any of the given glob patterns from the output of
+    /// `spawn_structured`, such as `**/package-lock.json`.  Paths are matched
+    /// relative to the repository root.  The raw output of `spawn` is not
+    /// filtered.
+    #[cfg(feature = "glob")]
+    pub fn ignore_paths<I, S>(&mut self, globs: I) -> Result<&mut Self, SvnError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut set = globset::GlobSetBuilder::new();
+        for glob in globs {
+            set.add(globset::Glob::new(glob.as_ref())?);
+        }
+
+        self.ignore = Some(set.build()?);
+        Ok(self)
+    }
+
+    /// Add raw options to the `-x` extensions string, such as `"-u -b"`,
+    /// alongside any set by the typed helpers.
+    pub fn extensions(&mut self, extensions: &str) -> &mut Self {
+        self.extensions.push(extensions.to_string());
         self
     }
 
-    pub fn spawn(&mut self) -> Result<SvnlookCommand, SvnError> {
+    pub fn revision<R: Into<Revision>>(&mut self, revision: R) -> &mut Self {
+        self.command.arg(format!("-r{}", revision.into()));
+        self
+    }
+
+    /// Append the combined extensions and the repository path, completing
+    /// the command line.
+    fn push_trailing_args(&mut self) {
+        if !self.extensions.is_empty() {
+            self.command.arg("-x").arg(self.extensions.join(" "));
+        }
+
         self.command.arg("--");
         self.command.arg(&self.repository);
+    }
+
+    pub fn spawn(&mut self) -> Result<SvnlookCommand, SvnError> {
+        self.push_trailing_args();
         SvnlookCommand::spawn(&mut self.command)
     }
+
+    /// Spawn the diff, parsing it into a stream of per-file diffs.
+    pub fn spawn_structured(&mut self) -> Result<SvnDiffIter, SvnError> {
+        let iter = SvnDiffIter::from(self.spawn()?).killable();
+
+        #[cfg(feature = "glob")]
+        let iter = match &self.ignore {
+            Some(ignore) => iter.ignoring(ignore.clone()),
+            None => iter,
+        };
+
+        Ok(iter)
+    }
+}
+
+/// Wrap a diff stream, stripping svnlook's `Index:`/`Modified:` headers,
+/// `====` rulers, property blocks, and binary file notices, leaving a plain
+/// unified diff suitable for `patch -p0`.
+///
+/// Input is processed a line at a time, so arbitrarily large diffs may be
+/// streamed.
+pub fn clean_diff<R: BufRead>(reader: R) -> impl Read {
+    CleanDiff {
+        reader,
+        line: vec![],
+        pos: 0,
+        in_hunk: false,
+        in_props: false,
+    }
+}
+
+#[derive(Debug)]
+struct CleanDiff<R> {
+    reader: R,
+    line: Vec<u8>,
+    pos: usize,
+    in_hunk: bool,
+    in_props: bool,
+}
+
+impl<R: BufRead> CleanDiff<R> {
+    fn keep(&mut self) -> bool {
+        let line = &self.line[..];
+
+        if self.in_hunk {
+            match line.first() {
+                Some(b' ') | Some(b'+') | Some(b'-') | Some(b'\\') => return true,
+                _ => self.in_hunk = false,
+            }
+        }
+
+        if line.starts_with(b"Property changes on: ") {
+            self.in_props = true;
+        } else if line.starts_with(b"===") {
+            self.in_props = false;
+        } else if !self.in_props {
+            if line.starts_with(b"@@") {
+                self.in_hunk = true;
+                return true;
+            }
+
+            return line.starts_with(b"--- ") || line.starts_with(b"+++ ");
+        }
+
+        false
+    }
+
+    fn fill_line(&mut self) -> io::Result<usize> {
+        self.pos = 0;
+
+        loop {
+            self.line.clear();
+
+            if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok(0);
+            }
+
+            if self.keep() {
+                return Ok(self.line.len());
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Read for CleanDiff<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.line.len() && self.fill_line()? == 0 {
+            return Ok(0);
+        }
+
+        let n = (&self.line[self.pos..]).read(buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    #[test]
+    fn argument_order() {
+        let mut builder = SvnDiffBuilder::new(Path::new("/repo"), Command::new("svnlook"));
+        builder
+            .no_diff_deleted()
+            .no_diff_added()
+            .revision(5)
+            .ignore_whitespace_change();
+        builder.push_trailing_args();
+
+        assert_eq!(
+            builder.command.get_args().collect::<Vec<_>>(),
+            [
+                "diff",
+                "--no-diff-deleted",
+                "--no-diff-added",
+                "-r5",
+                "-x",
+                "-b",
+                "--",
+                "/repo"
+            ]
+            .iter()
+            .map(OsStr::new)
+            .collect::<Vec<_>>()
+        );
+    }
+
+    const RULER: &str = "===================================================================\n";
+
+    #[test]
+    fn clean_diff_applies() {
+        let diff = format!(
+            "Modified: trunk/a\n{r}--- trunk/a\t(rev 1)\n+++ trunk/a\t(rev 2)\n\
+             @@ -1,2 +1,2 @@\n a\n-b\n+c\n\n\
+             Property changes on: trunk/a\n\
+             ___________________________________________________________________\n\
+             Added: svn:eol-style\n## -0,0 +1 ##\n+native\n\n\
+             Added: trunk/bin\n{r}Cannot display: file marked as a binary type.\n\
+             svn:mime-type = application/octet-stream\n\n\
+             Modified: trunk/b\n{r}--- trunk/b\t(rev 1)\n+++ trunk/b\t(rev 2)\n\
+             @@ -1 +1,2 @@\n x\n+y\n",
+            r = RULER
+        );
+
+        let mut cleaned = String::new();
+        clean_diff(diff.as_bytes())
+            .read_to_string(&mut cleaned)
+            .unwrap();
+        assert_eq!(
+            cleaned,
+            "--- trunk/a\t(rev 1)\n+++ trunk/a\t(rev 2)\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n\
+             --- trunk/b\t(rev 1)\n+++ trunk/b\t(rev 2)\n@@ -1 +1,2 @@\n x\n+y\n"
+        );
+
+        // Give each file in the cleaned diff back a header so it can be parsed
+        let rewrapped = cleaned.replace("--- ", &format!("Index: f\n{}--- ", RULER));
+        let diffs = SvnDiffIter::from(rewrapped.as_bytes())
+            .collect_diffs()
+            .unwrap();
+
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].apply(b"a\nb\n").unwrap(), b"a\nc\n");
+        assert_eq!(diffs[1].apply(b"x\n").unwrap(), b"x\ny\n");
+    }
 }
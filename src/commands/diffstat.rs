@@ -0,0 +1,276 @@
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+use super::filediff::parse_hunk_header;
+use crate::parallel::ParallelRevisions;
+use crate::{Repository, SvnError, SvnFileDiff};
+
+/// Counts of added and removed lines in a diff
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diffstat {
+    pub added: u64,
+    pub removed: u64,
+}
+
+impl Diffstat {
+    /// Count added and removed lines within the hunks of a diff stream.
+    ///
+    /// Each hunk is bounded by the line counts in its `@@` header, so file
+    /// headers, `Cannot display` messages for binary files, and property
+    /// blocks are never counted, even where they begin with `+` or `-`.
+    ///
+    /// Only the first few bytes of each line are retained, so memory use is
+    /// constant however large the diff or its individual lines.
+    pub fn from_reader<R: BufRead>(mut diff: R) -> Result<Self, SvnError> {
+        let mut stat = Self::default();
+        // Old and new lines remaining in the current hunk
+        let (mut old, mut new) = (0u64, 0u64);
+
+        while let Some((head, len)) = line_head(&mut diff)? {
+            let line = &head[..len];
+
+            if old > 0 || new > 0 {
+                match line.first() {
+                    Some(b'+') => {
+                        stat.added += 1;
+                        new = new.saturating_sub(1);
+                        continue;
+                    }
+                    Some(b'-') => {
+                        stat.removed += 1;
+                        old = old.saturating_sub(1);
+                        continue;
+                    }
+                    // Context, including empty lines with trailing space lost
+                    Some(b' ') | None => {
+                        old = old.saturating_sub(1);
+                        new = new.saturating_sub(1);
+                        continue;
+                    }
+                    Some(b'\\') => continue,
+                    // The hunk ended early, so treat this as a header
+                    _ => {
+                        old = 0;
+                        new = 0;
+                    }
+                }
+            }
+
+            if line.starts_with(b"@@") {
+                if let Ok(hunk) = parse_hunk_header(line, b"@@") {
+                    old = hunk.old_lines;
+                    new = hunk.new_lines;
+                }
+            }
+        }
+
+        Ok(stat)
+    }
+
+    /// Total the added and removed lines within the hunks of parsed file
+    /// diffs, so files dropped by a filter such as
+    /// `SvnDiffBuilder::ignore_paths` are not counted.  Property changes are
+    /// not counted.
+    pub fn from_diffs<I>(diffs: I) -> Result<Self, SvnError>
+    where
+        I: IntoIterator<Item = Result<SvnFileDiff, SvnError>>,
+    {
+        let mut stat = Self::default();
+
+        for diff in diffs {
+            let stats = diff?.stats();
+            stat.added += stats.added;
+            stat.removed += stats.removed;
+        }
+
+        Ok(stat)
+    }
+}
+
+/// Enough of a line to hold any hunk header
+const HEAD_LEN: usize = 128;
+
+/// Consume the next line, returning up to its first `HEAD_LEN` bytes, or
+/// `None` at the end of the stream.
+fn line_head<R: BufRead>(reader: &mut R) -> io::Result<Option<([u8; HEAD_LEN], usize)>> {
+    let mut head = [0; HEAD_LEN];
+    let mut len = 0;
+    let mut started = false;
+
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        if buf.is_empty() {
+            return Ok(if started { Some((head, len)) } else { None });
+        }
+        started = true;
+
+        let end = buf.iter().position(|&b| b == b'\n');
+        for &b in buf[..end.unwrap_or(buf.len())]
+            .iter()
+            .take(head.len() - len)
+        {
+            head[len] = b;
+            len += 1;
+        }
+
+        let consumed = end.map_or(buf.len(), |end| end + 1);
+        reader.consume(consumed);
+
+        if end.is_some() {
+            return Ok(Some((head, len)));
+        }
+    }
+}
+
+/// An iterator over per-revision diffstats, as returned by
+/// `Repository::diffstats_range`
+#[derive(Debug)]
+pub struct SvnDiffstatIter(ParallelRevisions<Diffstat>);
+
+impl SvnDiffstatIter {
+    pub(crate) fn new(repository: Repository, revisions: Range<u64>, concurrency: usize) -> Self {
+        Self(ParallelRevisions::new(
+            revisions,
+            concurrency,
+            move |revision| Diffstat::from_reader(repository.diff().revision(revision).spawn()?),
+        ))
+    }
+}
+
+impl Iterator for SvnDiffstatIter {
+    type Item = Result<(u64, Diffstat), SvnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Read};
+
+    use super::*;
+
+    /// Yields `pattern` `count` times without materialising the result
+    struct Repeated {
+        pattern: &'static [u8],
+        pos: usize,
+        count: usize,
+    }
+
+    impl Read for Repeated {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.count == 0 {
+                return Ok(0);
+            }
+
+            let n = buf.len().min(self.pattern.len() - self.pos);
+            buf[..n].copy_from_slice(&self.pattern[self.pos..self.pos + n]);
+            self.pos += n;
+
+            if self.pos == self.pattern.len() {
+                self.pos = 0;
+                self.count -= 1;
+            }
+
+            Ok(n)
+        }
+    }
+
+    fn repeated(pattern: &'static [u8], count: usize) -> Repeated {
+        Repeated {
+            pattern,
+            pos: 0,
+            count,
+        }
+    }
+
+    const DIFF: &str = "\
+Modified: trunk/a.txt
+===================================================================
+--- trunk/a.txt\t2020-01-01 00:00:00 UTC (rev 1)
++++ trunk/a.txt\t2020-01-02 00:00:00 UTC (rev 2)
+@@ -1,3 +1,4 @@
+ one
+--- x
++++ y
++four
+ three
+\\ No newline at end of file
+
+Added: trunk/b.bin
+===================================================================
+Cannot display: file marked as a binary type.
+svn:mime-type = application/octet-stream
++++ trunk/b.bin
+--- trunk/b.bin
+
+Modified: trunk/c.bin
+===================================================================
+(Binary files differ)
+
+Property changes on: trunk/a.txt
+___________________________________________________________________
+Added: svn:eol-style
+## -0,0 +1 ##
++native
+Deleted: svn:keywords
+## -1 +0,0 ##
+-Id
+";
+
+    #[test]
+    fn counts_only_hunk_lines() {
+        let stat = Diffstat::from_reader(DIFF.as_bytes()).unwrap();
+        assert_eq!(
+            stat,
+            Diffstat {
+                added: 2,
+                removed: 1
+            }
+        );
+    }
+
+    #[test]
+    fn long_lines_and_many_hunks() {
+        const LONG: usize = 64 << 20;
+        const HUNKS: usize = 100_000;
+
+        let diff = b"@@ -0,0 +1 @@\n+"
+            .chain(io::repeat(b'x').take(LONG as u64))
+            .chain(&b"\n"[..])
+            .chain(repeated(b"@@ -1,2 +1,2 @@\n-old\n+new\n same\n", HUNKS));
+
+        let stat = Diffstat::from_reader(BufReader::new(diff)).unwrap();
+        assert_eq!(
+            stat,
+            Diffstat {
+                added: HUNKS as u64 + 1,
+                removed: HUNKS as u64
+            }
+        );
+    }
+
+    #[test]
+    fn line_head_truncates() {
+        let mut diff = BufReader::new(
+            io::repeat(b'x')
+                .take(HEAD_LEN as u64 * 100)
+                .chain(&b"\nnext\n"[..]),
+        );
+
+        let (head, len) = line_head(&mut diff).unwrap().unwrap();
+        assert_eq!(len, HEAD_LEN);
+        assert!(head.iter().all(|&b| b == b'x'));
+
+        let (head, len) = line_head(&mut diff).unwrap().unwrap();
+        assert_eq!(&head[..len], b"next");
+        assert!(line_head(&mut diff).unwrap().is_none());
+    }
+}
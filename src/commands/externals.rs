@@ -0,0 +1,280 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::SvnError;
+
+/// A single `svn:externals` definition
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExternalDef {
+    pub local_path: PathBuf,
+    pub url: String,
+    pub peg_rev: Option<u64>,
+    pub operative_rev: Option<u64>,
+}
+
+/// Split a definition into tokens, honouring the double quotes and backslash
+/// escapes Subversion 1.6+ allows for paths containing spaces.
+fn tokenize(line: &str) -> Result<Vec<String>, SvnError> {
+    let mut tokens = vec![];
+    let mut token = String::new();
+    let mut in_token = false;
+    let mut quoted = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                token.push(chars.next().ok_or(SvnError::ParseError)?);
+                in_token = true;
+            }
+            '"' => {
+                quoted = !quoted;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut token));
+                    in_token = false;
+                }
+            }
+            c => {
+                token.push(c);
+                in_token = true;
+            }
+        }
+    }
+
+    if quoted {
+        return Err(SvnError::ParseError);
+    }
+
+    if in_token {
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn is_url(s: &str) -> bool {
+    s.contains("://")
+}
+
+fn is_relative_url(s: &str) -> bool {
+    s.starts_with("^/") || s.starts_with("../") || s.starts_with("//") || s.starts_with('/')
+}
+
+/// Parse a revision number, treating `HEAD` as unspecified.
+fn parse_revision(rev: &str) -> Result<Option<u64>, SvnError> {
+    match rev {
+        "HEAD" => Ok(None),
+        rev => Ok(Some(rev.parse()?)),
+    }
+}
+
+/// Split an optional `@PEG` suffix from a URL.
+fn split_peg(url: &str) -> Result<(&str, Option<u64>), SvnError> {
+    match url.rfind('@') {
+        Some(pos) if !url[pos + 1..].contains('/') => {
+            Ok((&url[..pos], parse_revision(&url[pos + 1..])?))
+        }
+        _ => Ok((url, None)),
+    }
+}
+
+impl FromStr for ExternalDef {
+    type Err = SvnError;
+
+    /// Parse either the pre-1.5 `LOCAL_PATH [-r REV] URL` format or the newer
+    /// `[-r REV] URL[@PEG] LOCAL_PATH` format.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut tokens = tokenize(line)?;
+        let mut operative_rev = None;
+
+        if let Some(pos) = tokens.iter().position(|t| t.starts_with("-r")) {
+            let rev = if tokens[pos].len() > 2 {
+                tokens.remove(pos)[2..].to_string()
+            } else if pos + 1 < tokens.len() {
+                tokens.remove(pos);
+                tokens.remove(pos)
+            } else {
+                return Err(SvnError::ParseError);
+            };
+            operative_rev = parse_revision(&rev)?;
+        }
+
+        if tokens.len() != 2 {
+            return Err(SvnError::ParseError);
+        }
+
+        let (first, second) = (&tokens[0], &tokens[1]);
+
+        if is_url(first) || is_relative_url(first) {
+            let (url, peg_rev) = split_peg(first)?;
+            Ok(ExternalDef {
+                local_path: PathBuf::from(second),
+                url: url.to_string(),
+                peg_rev,
+                operative_rev: operative_rev.or(peg_rev),
+            })
+        } else if is_url(second) {
+            Ok(ExternalDef {
+                local_path: PathBuf::from(first),
+                url: second.to_string(),
+                peg_rev: operative_rev,
+                operative_rev,
+            })
+        } else {
+            Err(SvnError::ParseError)
+        }
+    }
+}
+
+/// Parse the value of an `svn:externals` property, skipping blank lines and
+/// `#` comments.
+pub fn parse_externals(externals: &str) -> Result<Vec<ExternalDef>, SvnError> {
+    externals
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ExternalDef::from_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(
+        local_path: &str,
+        url: &str,
+        peg_rev: Option<u64>,
+        operative_rev: Option<u64>,
+    ) -> ExternalDef {
+        ExternalDef {
+            local_path: PathBuf::from(local_path),
+            url: url.to_string(),
+            peg_rev,
+            operative_rev,
+        }
+    }
+
+    #[test]
+    fn definitions() {
+        let cases = [
+            // Pre-1.5
+            (
+                "sounds http://host/sounds",
+                def("sounds", "http://host/sounds", None, None),
+            ),
+            (
+                "skins -r148 http://host/skins",
+                def("skins", "http://host/skins", Some(148), Some(148)),
+            ),
+            (
+                "skins -r 21 http://host/skins",
+                def("skins", "http://host/skins", Some(21), Some(21)),
+            ),
+            (
+                "skins -rHEAD http://host/skins",
+                def("skins", "http://host/skins", None, None),
+            ),
+            // 1.5+
+            (
+                "http://host/sounds sounds",
+                def("sounds", "http://host/sounds", None, None),
+            ),
+            (
+                "-r148 http://host/skins skins",
+                def("skins", "http://host/skins", None, Some(148)),
+            ),
+            (
+                "-r 21 http://host/skins skins",
+                def("skins", "http://host/skins", None, Some(21)),
+            ),
+            (
+                "http://host/skins@21 skins",
+                def("skins", "http://host/skins", Some(21), Some(21)),
+            ),
+            (
+                "-r 20 http://host/skins@21 skins",
+                def("skins", "http://host/skins", Some(21), Some(20)),
+            ),
+            (
+                "http://host/skins@HEAD skins",
+                def("skins", "http://host/skins", None, None),
+            ),
+            // User names are not peg revisions
+            (
+                "svn+ssh://user@host/lib lib",
+                def("lib", "svn+ssh://user@host/lib", None, None),
+            ),
+            (
+                "svn+ssh://user@host/lib@7 lib",
+                def("lib", "svn+ssh://user@host/lib", Some(7), Some(7)),
+            ),
+            // Relative URLs
+            ("../../lib lib", def("lib", "../../lib", None, None)),
+            (
+                "^/trunk/lib@3 lib",
+                def("lib", "^/trunk/lib", Some(3), Some(3)),
+            ),
+            ("//host/lib lib", def("lib", "//host/lib", None, None)),
+            ("/repos/lib lib", def("lib", "/repos/lib", None, None)),
+            // Quoting and escapes
+            (
+                "http://host/lib \"my lib\"",
+                def("my lib", "http://host/lib", None, None),
+            ),
+            (
+                "http://host/lib my\\ lib",
+                def("my lib", "http://host/lib", None, None),
+            ),
+            (
+                "\"^/trunk/my lib\" lib",
+                def("lib", "^/trunk/my lib", None, None),
+            ),
+            (
+                "  http://host/lib\tlib  ",
+                def("lib", "http://host/lib", None, None),
+            ),
+        ];
+
+        for (line, expected) in &cases {
+            assert_eq!(&line.parse::<ExternalDef>().unwrap(), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn malformed_definitions() {
+        let cases = [
+            "",
+            "lib",
+            "lib other",
+            "http://host/lib lib extra",
+            "lib -r",
+            "-rX http://host/lib lib",
+            "http://host/lib@x lib",
+            "http://host/lib \"lib",
+            "http://host/lib lib\\",
+        ];
+
+        for line in &cases {
+            assert!(line.parse::<ExternalDef>().is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn property_value() {
+        let externals = "# vendor code\n\nhttp://host/a a\n  \n-r3 ^/b b\n";
+
+        assert_eq!(
+            parse_externals(externals).unwrap(),
+            [
+                def("a", "http://host/a", None, None),
+                def("b", "^/b", None, Some(3)),
+            ]
+        );
+        assert!(parse_externals("http://host/a a\nbogus\n").is_err());
+    }
+}
@@ -0,0 +1,830 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::str;
+
+use super::{collect_or_kill, strip_cr};
+use crate::{SvnError, SvnlookCommand};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// A single line within a hunk, with its `+`/`-`/` ` prefix and trailing
+/// newline removed
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// The raw bytes of the line.  Only the `\n` terminator is removed, so a
+    /// CRLF line retains its trailing `\r` and is reproduced by `write_to`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_lossy"))]
+    pub content: Vec<u8>,
+    /// Set if followed by `\ No newline at end of file`
+    pub missing_newline: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Hunk {
+    pub old_start: u64,
+    pub old_lines: u64,
+    pub new_start: u64,
+    pub new_lines: u64,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SvnDiffKind {
+    Added,
+    Deleted,
+    Modified,
+    Copied,
+}
+
+/// A change to a single property, with its old and new values as hunks
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PropertyDiff {
+    pub name: String,
+    pub kind: SvnDiffKind,
+    pub hunks: Vec<Hunk>,
+}
+
+/// The textual and property changes to a single file within a diff
+///
+/// With `SvnDiffBuilder::properties_only`, `hunks` is always empty and only
+/// `properties` is populated.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SvnFileDiff {
+    pub path: PathBuf,
+    /// Taken from svnlook's `Added:`/`Deleted:`/`Copied:` headers.  Files
+    /// under `Modified:` or plain `Index:` headers are `Modified`.
+    pub kind: SvnDiffKind,
+    pub hunks: Vec<Hunk>,
+    pub properties: Vec<PropertyDiff>,
+}
+
+/// Per-file diff counts, as returned by `SvnFileDiff::stats`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FileDiffStats {
+    pub hunks: usize,
+    pub added: u64,
+    pub removed: u64,
+}
+
+#[cfg(feature = "serde")]
+fn serialize_lossy<S: serde::Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&String::from_utf8_lossy(bytes))
+}
+
+fn chomp(line: &[u8]) -> &[u8] {
+    if line.ends_with(b"\n") {
+        &line[..line.len() - 1]
+    } else {
+        line
+    }
+}
+
+fn push_line(out: &mut Vec<u8>, content: &[u8], newline: bool) {
+    out.extend_from_slice(content);
+    if newline {
+        out.push(b'\n');
+    }
+}
+
+/// Split data into lines, noting whether each ends with a newline.
+fn split_lines(data: &[u8]) -> Vec<(&[u8], bool)> {
+    let mut lines = vec![];
+    let mut rest = data;
+
+    while !rest.is_empty() {
+        match rest.iter().position(|&b| b == b'\n') {
+            Some(end) => {
+                lines.push((&rest[..end], true));
+                rest = &rest[end + 1..];
+            }
+            None => {
+                lines.push((rest, false));
+                break;
+            }
+        }
+    }
+
+    lines
+}
+
+fn parse_range(range: &[u8]) -> Result<(u64, u64), SvnError> {
+    let range = str::from_utf8(range)?;
+    match range.find(',') {
+        Some(pos) => Ok((range[..pos].parse()?, range[pos + 1..].parse()?)),
+        None => Ok((range.parse()?, 1)),
+    }
+}
+
+/// Parse the ranges from a `@@ -l,s +l,s @@` hunk header, or the equivalent
+/// `##` property header.
+pub(crate) fn parse_hunk_header(line: &[u8], marker: &[u8]) -> Result<Hunk, SvnError> {
+    let mut fields = line
+        .strip_prefix(marker)
+        .ok_or(SvnError::ParseError)?
+        .split(|&b| b == b' ')
+        .filter(|f| !f.is_empty());
+
+    let old = fields
+        .next()
+        .and_then(|f| f.strip_prefix(b"-"))
+        .ok_or(SvnError::ParseError)?;
+    let new = fields
+        .next()
+        .and_then(|f| f.strip_prefix(b"+"))
+        .ok_or(SvnError::ParseError)?;
+
+    let (old_start, old_lines) = parse_range(old)?;
+    let (new_start, new_lines) = parse_range(new)?;
+
+    Ok(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: vec![],
+    })
+}
+
+fn file_header(line: &[u8]) -> Option<(SvnDiffKind, &[u8])> {
+    [
+        (SvnDiffKind::Modified, &b"Index: "[..]),
+        (SvnDiffKind::Modified, b"Modified: "),
+        (SvnDiffKind::Added, b"Added: "),
+        (SvnDiffKind::Deleted, b"Deleted: "),
+        (SvnDiffKind::Copied, b"Copied: "),
+    ]
+    .iter()
+    .find_map(|(kind, prefix)| line.strip_prefix(*prefix).map(|path| (*kind, path)))
+}
+
+/// The path from a file header, without the ` (from rev N, path)` suffix
+/// svnlook gives copies.
+fn header_path(path: &[u8]) -> PathBuf {
+    let is_copy_source = |tail: &[u8]| {
+        tail.strip_prefix(b" (from rev ")
+            .and_then(|tail| {
+                let digits = tail.iter().take_while(|b| b.is_ascii_digit()).count();
+                tail[digits..].strip_prefix(b", ").filter(|_| digits > 0)
+            })
+            .is_some_and(|source| source.ends_with(b")"))
+    };
+    let path = match (0..path.len()).find(|&pos| is_copy_source(&path[pos..])) {
+        Some(pos) => &path[..pos],
+        None => path,
+    };
+
+    PathBuf::from(String::from_utf8_lossy(path).to_string())
+}
+
+/// Collect the path of each file header in a diff stream, in order.  Files
+/// with only property changes have no file header and are not included,
+/// while binary files are.
+pub fn diff_file_paths<R: BufRead>(mut reader: R) -> Result<Vec<PathBuf>, SvnError> {
+    let mut paths = vec![];
+    let mut header = None;
+    let mut line = vec![];
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(paths);
+        }
+
+        let text = strip_cr(chomp(&line));
+
+        // Property sections use the same style of header, but only file
+        // headers are followed by a ruler.
+        if text.starts_with(b"===") {
+            paths.extend(header.take());
+        } else {
+            header = file_header(text).map(|(_, path)| header_path(path));
+        }
+    }
+}
+
+impl DiffLine {
+    /// The line content as text, with invalid UTF-8 replaced by U+FFFD.
+    pub fn content_str_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.content)
+    }
+}
+
+impl Hunk {
+    /// The hunk which undoes this one.
+    pub fn reversed(&self) -> Self {
+        Hunk {
+            old_start: self.new_start,
+            old_lines: self.new_lines,
+            new_start: self.old_start,
+            new_lines: self.old_lines,
+            lines: self
+                .lines
+                .iter()
+                .map(|line| DiffLine {
+                    kind: match line.kind {
+                        DiffLineKind::Context => DiffLineKind::Context,
+                        DiffLineKind::Added => DiffLineKind::Removed,
+                        DiffLineKind::Removed => DiffLineKind::Added,
+                    },
+                    content: line.content.clone(),
+                    missing_newline: line.missing_newline,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_lines, self.new_start, self.new_lines
+        )?;
+
+        for line in &self.lines {
+            w.write_all(match line.kind {
+                DiffLineKind::Context => b" ",
+                DiffLineKind::Added => b"+",
+                DiffLineKind::Removed => b"-",
+            })?;
+            w.write_all(&line.content)?;
+            w.write_all(b"\n")?;
+            if line.missing_newline {
+                w.write_all(b"\\ No newline at end of file\n")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SvnFileDiff {
+    /// The diff which undoes this one.
+    pub fn reversed(&self) -> Self {
+        SvnFileDiff {
+            path: self.path.clone(),
+            kind: match self.kind {
+                SvnDiffKind::Added | SvnDiffKind::Copied => SvnDiffKind::Deleted,
+                SvnDiffKind::Deleted => SvnDiffKind::Added,
+                SvnDiffKind::Modified => SvnDiffKind::Modified,
+            },
+            hunks: self.hunks.iter().map(Hunk::reversed).collect(),
+            properties: self
+                .properties
+                .iter()
+                .map(|prop| PropertyDiff {
+                    name: prop.name.clone(),
+                    kind: match prop.kind {
+                        SvnDiffKind::Added | SvnDiffKind::Copied => SvnDiffKind::Deleted,
+                        SvnDiffKind::Deleted => SvnDiffKind::Added,
+                        SvnDiffKind::Modified => SvnDiffKind::Modified,
+                    },
+                    hunks: prop.hunks.iter().map(Hunk::reversed).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Count the hunks and added and removed lines.  Property changes are
+    /// not counted.
+    pub fn stats(&self) -> FileDiffStats {
+        let mut stats = FileDiffStats {
+            hunks: self.hunks.len(),
+            ..FileDiffStats::default()
+        };
+
+        for line in self.hunks.iter().flat_map(|hunk| &hunk.lines) {
+            match line.kind {
+                DiffLineKind::Added => stats.added += 1,
+                DiffLineKind::Removed => stats.removed += 1,
+                DiffLineKind::Context => (),
+            }
+        }
+
+        stats
+    }
+
+    /// Apply the hunks to `base`, the file's contents before the change.
+    /// Returns `None` if a context or removed line doesn't match `base`.
+    pub fn apply(&self, base: &[u8]) -> Option<Vec<u8>> {
+        let base = split_lines(base);
+        let mut out = vec![];
+        let mut pos = 0;
+
+        for hunk in &self.hunks {
+            // An insertion at the start of the file has an old_start of 0
+            let start = usize::try_from(hunk.old_start).ok()?;
+            let start = if hunk.old_lines == 0 {
+                start
+            } else {
+                start.checked_sub(1)?
+            };
+
+            for (content, newline) in base.get(pos..start)? {
+                push_line(&mut out, content, *newline);
+            }
+            pos = start;
+
+            for line in &hunk.lines {
+                match line.kind {
+                    DiffLineKind::Context | DiffLineKind::Removed => {
+                        let (content, _) = base.get(pos)?;
+                        if *content != &line.content[..] {
+                            return None;
+                        }
+                        pos += 1;
+
+                        if line.kind == DiffLineKind::Context {
+                            push_line(&mut out, content, !line.missing_newline);
+                        }
+                    }
+                    DiffLineKind::Added => {
+                        push_line(&mut out, &line.content, !line.missing_newline)
+                    }
+                }
+            }
+        }
+
+        for (content, newline) in &base[pos..] {
+            push_line(&mut out, content, *newline);
+        }
+
+        Some(out)
+    }
+
+    /// Write this diff in unified format, suitable for `patch -p0`.  Property
+    /// changes are not included.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.hunks.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(w, "--- {}", self.path.display())?;
+        writeln!(w, "+++ {}", self.path.display())?;
+        for hunk in &self.hunks {
+            hunk.write_to(w)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A streaming iterator over the files in a diff, as returned by
+/// `SvnDiffBuilder::spawn_structured`
+///
+/// Only one file is held in memory at a time.  Files with only property
+/// changes are reported with empty `hunks`.
+#[derive(Debug)]
+pub struct SvnDiffIter<R = SvnlookCommand> {
+    reader: R,
+    line: Vec<u8>,
+    peeked: bool,
+    current: Option<SvnFileDiff>,
+    in_props: bool,
+    /// Set once the reader reports EOF, as `SvnlookCommand` fails reads
+    /// after reaping svnlook
+    eof: bool,
+    finished: bool,
+    /// Stops the reader early, if it is a process which can be stopped
+    killer: Option<fn(&mut R)>,
+    #[cfg(feature = "glob")]
+    ignore: Option<globset::GlobSet>,
+}
+
+impl<R: BufRead> From<R> for SvnDiffIter<R> {
+    fn from(reader: R) -> Self {
+        Self {
+            reader,
+            line: vec![],
+            peeked: false,
+            current: None,
+            in_props: false,
+            eof: false,
+            finished: false,
+            killer: None,
+            #[cfg(feature = "glob")]
+            ignore: None,
+        }
+    }
+}
+
+impl SvnDiffIter {
+    pub(crate) fn killable(mut self) -> Self {
+        self.killer = Some(|svnlook| {
+            let _ = svnlook.kill();
+        });
+        self
+    }
+}
+
+impl<R: BufRead> SvnDiffIter<R> {
+    /// Invert each file diff, producing a diff which undoes the revision.
+    pub fn reverse(self) -> impl Iterator<Item = Result<SvnFileDiff, SvnError>> {
+        self.map(|diff| diff.map(|diff| diff.reversed()))
+    }
+
+    /// Only yield diffs of added files, which svnlook cannot do itself.
+    pub fn added_only(self) -> impl Iterator<Item = Result<SvnFileDiff, SvnError>> {
+        self.filter(|diff| {
+            diff.as_ref()
+                .map_or(true, |diff| diff.kind == SvnDiffKind::Added)
+        })
+    }
+
+    /// Only yield diffs of deleted files, which svnlook cannot do itself.
+    pub fn deleted_only(self) -> impl Iterator<Item = Result<SvnFileDiff, SvnError>> {
+        self.filter(|diff| {
+            diff.as_ref()
+                .map_or(true, |diff| diff.kind == SvnDiffKind::Deleted)
+        })
+    }
+
+    /// Skip files whose paths match `ignore`, as if they were not in the diff.
+    #[cfg(feature = "glob")]
+    pub fn ignoring(mut self, ignore: globset::GlobSet) -> Self {
+        self.ignore = Some(ignore);
+        self
+    }
+
+    #[cfg(feature = "glob")]
+    fn ignored(&self, diff: &SvnFileDiff) -> bool {
+        self.ignore
+            .as_ref()
+            .is_some_and(|ignore| ignore.is_match(&diff.path))
+    }
+
+    #[cfg(not(feature = "glob"))]
+    fn ignored(&self, _diff: &SvnFileDiff) -> bool {
+        false
+    }
+
+    fn kill(&mut self) {
+        self.finished = true;
+        if let Some(killer) = self.killer {
+            killer(&mut self.reader);
+        }
+    }
+
+    /// Consume the iterator into a `Vec`, stopping at the first error.
+    pub fn collect_diffs(self) -> Result<Vec<SvnFileDiff>, SvnError> {
+        collect_or_kill(self, Self::kill)
+    }
+
+    fn read_line(&mut self) -> Result<bool, SvnError> {
+        if self.peeked {
+            self.peeked = false;
+            return Ok(true);
+        }
+
+        self.line.clear();
+        if self.eof {
+            return Ok(false);
+        }
+
+        self.eof = self.reader.read_until(b'\n', &mut self.line)? == 0;
+        Ok(!self.eof)
+    }
+
+    /// Read the body of a hunk, using the line counts from its header to
+    /// determine where it ends.
+    fn read_hunk(&mut self, mut hunk: Hunk) -> Result<Hunk, SvnError> {
+        let (mut old, mut new) = (hunk.old_lines, hunk.new_lines);
+
+        while old > 0 || new > 0 || self.peeked_no_newline()? {
+            if !self.read_line()? {
+                return Err(SvnError::ParseError);
+            }
+
+            let line = chomp(&self.line);
+            let kind = match line.first() {
+                Some(b' ') if old > 0 && new > 0 => {
+                    old -= 1;
+                    new -= 1;
+                    DiffLineKind::Context
+                }
+                Some(b'-') if old > 0 => {
+                    old -= 1;
+                    DiffLineKind::Removed
+                }
+                Some(b'+') if new > 0 => {
+                    new -= 1;
+                    DiffLineKind::Added
+                }
+                Some(b'\\') => {
+                    let last = hunk.lines.last_mut().ok_or(SvnError::ParseError)?;
+                    last.missing_newline = true;
+                    continue;
+                }
+                _ => return Err(SvnError::ParseError),
+            };
+
+            hunk.lines.push(DiffLine {
+                kind,
+                content: line[1..].to_vec(),
+                missing_newline: false,
+            });
+        }
+
+        Ok(hunk)
+    }
+
+    /// Check for a trailing `\ No newline at end of file` marker, leaving the
+    /// line to be read again.
+    fn peeked_no_newline(&mut self) -> Result<bool, SvnError> {
+        if !self.read_line()? {
+            return Ok(false);
+        }
+
+        self.peeked = true;
+        Ok(self.line.starts_with(b"\\"))
+    }
+
+    /// Start a new file, returning the previous one if any.
+    fn start_file(&mut self, path: PathBuf, kind: SvnDiffKind) -> Option<SvnFileDiff> {
+        self.current.replace(SvnFileDiff {
+            path,
+            kind,
+            hunks: vec![],
+            properties: vec![],
+        })
+    }
+
+    fn parse(&mut self) -> Result<Option<SvnFileDiff>, SvnError> {
+        loop {
+            if !self.read_line()? {
+                return Ok(self.current.take());
+            }
+
+            let line = strip_cr(chomp(&self.line));
+
+            if line.starts_with(b"@@") {
+                let hunk = parse_hunk_header(line, b"@@")?;
+                let hunk = self.read_hunk(hunk)?;
+                self.current
+                    .as_mut()
+                    .ok_or(SvnError::ParseError)?
+                    .hunks
+                    .push(hunk);
+            } else if line.starts_with(b"##") {
+                let hunk = parse_hunk_header(line, b"##")?;
+                let hunk = self.read_hunk(hunk)?;
+                self.current
+                    .as_mut()
+                    .and_then(|diff| diff.properties.last_mut())
+                    .ok_or(SvnError::ParseError)?
+                    .hunks
+                    .push(hunk);
+            } else if let Some(path) = line.strip_prefix(b"Property changes on: ") {
+                self.in_props = true;
+
+                // Files with only property changes have no other header
+                let path = PathBuf::from(String::from_utf8_lossy(path).to_string());
+
+                if self.current.as_ref().map(|diff| &diff.path) != Some(&path) {
+                    if let Some(diff) = self.start_file(path, SvnDiffKind::Modified) {
+                        return Ok(Some(diff));
+                    }
+                }
+            } else if let Some((kind, path)) = file_header(line) {
+                let path = path.to_vec();
+
+                // Property sections use the same "Added: " style headers, but
+                // only file headers are followed by a ruler.
+                if !self.read_line()? {
+                    return Ok(self.current.take());
+                }
+
+                if !self.line.starts_with(b"===") {
+                    self.peeked = true;
+
+                    if self.in_props {
+                        self.current
+                            .as_mut()
+                            .ok_or(SvnError::ParseError)?
+                            .properties
+                            .push(PropertyDiff {
+                                name: String::from_utf8_lossy(&path).to_string(),
+                                kind,
+                                hunks: vec![],
+                            });
+                    }
+                    continue;
+                }
+
+                self.in_props = false;
+
+                let path = header_path(&path);
+                if let Some(diff) = self.start_file(path, kind) {
+                    return Ok(Some(diff));
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SvnDiffIter<R> {
+    type Item = Result<SvnFileDiff, SvnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.finished {
+                return None;
+            }
+
+            match self.parse() {
+                Ok(Some(diff)) if self.ignored(&diff) => continue,
+                Ok(Some(diff)) => return Some(Ok(diff)),
+                Ok(None) => {
+                    self.finished = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Parse a diff stream into a JSON array of file diffs.
+#[cfg(feature = "json")]
+pub fn diff_to_json<R: BufRead>(reader: R) -> Result<serde_json::Value, SvnError> {
+    SvnDiffIter::from(reader)
+        .map(|diff| Ok(serde_json::to_value(diff?)?))
+        .collect::<Result<Vec<_>, SvnError>>()
+        .map(serde_json::Value::Array)
+}
+
+/// Parse a diff stream and write it as a JSON array of file diffs, holding
+/// only one file in memory at a time.
+#[cfg(feature = "json")]
+pub fn write_diff_json<R: BufRead, W: Write>(reader: R, mut writer: W) -> Result<(), SvnError> {
+    writer.write_all(b"[")?;
+
+    for (i, diff) in SvnDiffIter::from(reader).enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut writer, &diff?)?;
+    }
+
+    writer.write_all(b"]")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULER: &[u8] = b"===================================================================\n";
+
+    fn parse(diff: &[u8]) -> Vec<SvnFileDiff> {
+        SvnDiffIter::from(diff).collect_diffs().unwrap()
+    }
+
+    fn modified(body: &[u8]) -> SvnFileDiff {
+        let mut input = b"Modified: f\n".to_vec();
+        input.extend_from_slice(RULER);
+        input.extend_from_slice(body);
+
+        let mut diffs = parse(&input);
+        assert_eq!(diffs.len(), 1);
+        diffs.remove(0)
+    }
+
+    fn assert_round_trip(body: &[u8], base: &[u8], new: &[u8]) {
+        let diff = modified(body);
+        assert_eq!(diff.apply(base).as_deref(), Some(new));
+        assert_eq!(diff.reversed().apply(new).as_deref(), Some(base));
+    }
+
+    #[test]
+    fn crlf_content_preserved() {
+        let body = b"--- f\n+++ f\n@@ -1,2 +1,2 @@\n a\r\n-b\r\n+line\r\n";
+        let mut input = b"Modified: f\n".to_vec();
+        input.extend_from_slice(RULER);
+        input.extend_from_slice(body);
+
+        let diffs = parse(&input);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].hunks[0].lines[2].content, b"line\r");
+
+        let mut out = vec![];
+        diffs[0].write_to(&mut out).unwrap();
+        assert_eq!(out, &body[..]);
+    }
+
+    #[test]
+    fn apply_and_reverse() {
+        assert_round_trip(
+            b"--- f\n+++ f\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n@@ -5,1 +5,2 @@\n e\n+f\n",
+            b"a\nb\nc\nd\ne\n",
+            b"a\nB\nc\nd\ne\nf\n",
+        );
+    }
+
+    #[test]
+    fn apply_and_reverse_missing_newline() {
+        // The old side lacks a trailing newline, which the change adds
+        assert_round_trip(
+            b"--- f\n+++ f\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+c\n",
+            b"a\nb",
+            b"a\nc\n",
+        );
+
+        // Neither side has one, and the unterminated line is context
+        assert_round_trip(
+            b"--- f\n+++ f\n@@ -1,2 +1,2 @@\n-a\n+x\n b\n\\ No newline at end of file\n",
+            b"a\nb",
+            b"x\nb",
+        );
+    }
+
+    #[test]
+    fn apply_rejects_mismatched_base() {
+        let diff = modified(b"--- f\n+++ f\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n");
+        assert_eq!(diff.apply(b"a\nz\n"), None);
+    }
+
+    #[test]
+    fn crlf_headers_match_lf() {
+        // svnlook on Windows ends its own lines with CRLF, leaving file and
+        // property content untouched
+        let lf = format!(
+            "Modified: f\n{}--- f\t(rev 1)\n+++ f\t(rev 2)\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n\n\
+             Property changes on: f\n\
+             ___________________________________________________________________\n\
+             Added: svn:eol-style\n## -0,0 +1 ##\n+native\n",
+            str::from_utf8(RULER).unwrap()
+        );
+        let crlf = format!(
+            "Modified: f\r\n{}\r\n--- f\t(rev 1)\r\n+++ f\t(rev 2)\r\n@@ -1,2 +1,2 @@\r\n a\n-b\n+c\n\r\n\
+             Property changes on: f\r\n\
+             ___________________________________________________________________\r\n\
+             Added: svn:eol-style\r\n## -0,0 +1 ##\r\n+native\n",
+            str::from_utf8(RULER).unwrap().trim_end()
+        );
+
+        let expected = parse(lf.as_bytes());
+        assert_eq!(expected[0].hunks[0].lines.len(), 3);
+        assert_eq!(expected[0].properties[0].name, "svn:eol-style");
+        assert_eq!(parse(crlf.as_bytes()), expected);
+    }
+
+    #[test]
+    fn kind_from_headers() {
+        let ruler = str::from_utf8(RULER).unwrap();
+        let diff = format!(
+            "Added: a\n{r}--- a\t(rev 0)\n+++ a\t(rev 2)\n@@ -0,0 +1 @@\n+a\n\n\
+             Deleted: b\n{r}--- b\t(rev 1)\n+++ b\t(rev 2)\n@@ -1 +0,0 @@\n-b\n\n\
+             Copied: c (from rev 1, a)\n{r}--- c\t(rev 1)\n+++ c\t(rev 2)\n@@ -1 +1 @@\n-a\n+c\n\n\
+             Index: d\n{r}--- d\t(revision 0)\n+++ d\t(revision 2)\n@@ -0,0 +1 @@\n+d\n",
+            r = ruler
+        );
+
+        let kinds = parse(diff.as_bytes())
+            .into_iter()
+            .map(|diff| (diff.path, diff.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            [
+                (PathBuf::from("a"), SvnDiffKind::Added),
+                (PathBuf::from("b"), SvnDiffKind::Deleted),
+                (PathBuf::from("c"), SvnDiffKind::Copied),
+                (PathBuf::from("d"), SvnDiffKind::Modified),
+            ]
+        );
+    }
+
+    #[test]
+    fn header_paths() {
+        assert_eq!(header_path(b"c (from rev 1, a)"), PathBuf::from("c"));
+        assert_eq!(
+            header_path(b"c (from rev 12, a (from rev 1, b))"),
+            PathBuf::from("c")
+        );
+        assert_eq!(header_path(b"c (from a)"), PathBuf::from("c (from a)"));
+        assert_eq!(
+            header_path(b"c (from rev x, a)"),
+            PathBuf::from("c (from rev x, a)")
+        );
+        assert_eq!(header_path(b"c"), PathBuf::from("c"));
+    }
+}
@@ -0,0 +1,30 @@
+use std::ops::Range;
+
+use crate::parallel::ParallelRevisions;
+use crate::{Repository, SvnError};
+
+/// An iterator over per-revision fingerprints, as returned by
+/// `Repository::fingerprint_range`
+///
+/// Comparing the sequences from two mirrors finds the first revision at
+/// which they diverge.
+#[derive(Debug)]
+pub struct SvnFingerprintIter(ParallelRevisions<[u8; 32]>);
+
+impl SvnFingerprintIter {
+    pub(crate) fn new(repository: Repository, revisions: Range<u64>, concurrency: usize) -> Self {
+        Self(ParallelRevisions::new(
+            revisions,
+            concurrency,
+            move |revision| repository.revision_fingerprint(revision),
+        ))
+    }
+}
+
+impl Iterator for SvnFingerprintIter {
+    type Item = Result<(u64, [u8; 32]), SvnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
@@ -0,0 +1,197 @@
+use std::convert::TryFrom;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::str::{self, FromStr};
+
+use super::{collect_or_kill, try_chomp};
+use crate::{Revision, SvnError, SvnlookCommand};
+
+/// A revision in which a node changed, and its path at that revision
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SvnHistoryEntry {
+    pub revision: u64,
+    /// The path relative to the repository root, without svnlook's leading
+    /// slash
+    pub path: PathBuf,
+}
+
+impl TryFrom<&[u8]> for SvnHistoryEntry {
+    type Error = SvnError;
+
+    fn try_from(line: &[u8]) -> Result<Self, Self::Error> {
+        let line = try_chomp(line)?;
+        let start = line
+            .iter()
+            .position(|&b| b != b' ')
+            .ok_or(SvnError::ParseError)?;
+        let line = &line[start..];
+        let end = line
+            .iter()
+            .position(|&b| b == b' ')
+            .ok_or(SvnError::ParseError)?;
+        let (revision, path) = line.split_at(end);
+        let path = &path[path
+            .iter()
+            .position(|&b| b != b' ')
+            .ok_or(SvnError::ParseError)?..];
+        let path = path.strip_prefix(b"/").unwrap_or(path);
+
+        Ok(SvnHistoryEntry {
+            revision: u64::from_str(str::from_utf8(revision)?)?,
+            path: PathBuf::from(String::from_utf8_lossy(path).to_string()),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct SvnHistoryBuilder {
+    repository: PathBuf,
+    command: Command,
+    path: Option<PathBuf>,
+}
+
+impl SvnHistoryBuilder {
+    pub(crate) fn new(repository: &Path, mut command: Command) -> Self {
+        command.arg("history");
+
+        Self {
+            repository: repository.to_path_buf(),
+            command,
+            path: None,
+        }
+    }
+
+    /// Start from the given revision rather than the youngest.
+    pub fn revision<R: Into<Revision>>(&mut self, revision: R) -> &mut Self {
+        self.command.arg(format!("-r{}", revision.into()));
+        self
+    }
+
+    pub fn limit(&mut self, limit: u64) -> &mut Self {
+        self.command.arg(format!("--limit={}", limit));
+        self
+    }
+
+    /// Report the history of `path` rather than the repository root.
+    pub fn path<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    fn push_trailing_args(&mut self) {
+        self.command.arg("--");
+        self.command.arg(&self.repository);
+        if let Some(path) = &self.path {
+            self.command.arg(path);
+        }
+    }
+
+    pub fn spawn(&mut self) -> Result<SvnHistoryIter, SvnError> {
+        self.push_trailing_args();
+        Ok(SvnHistoryIter::from(SvnlookCommand::spawn(
+            &mut self.command,
+        )?))
+    }
+
+    /// Run svnlook to completion, capturing its stderr so callers can tell
+    /// failures apart.  Entries are parsed with `parse_history`.
+    pub(crate) fn output(&mut self) -> Result<Output, SvnError> {
+        self.push_trailing_args();
+        Ok(self.command.output()?)
+    }
+}
+
+/// Whether a line of history output is one of the REVISION/PATH column
+/// headings rather than an entry
+fn is_heading(line: &[u8]) -> bool {
+    !line.iter().any(u8::is_ascii_digit)
+}
+
+/// Parse complete `svnlook history` output, as captured by
+/// `SvnHistoryBuilder::output`.
+pub(crate) fn parse_history(output: &[u8]) -> Result<Vec<SvnHistoryEntry>, SvnError> {
+    output
+        .split_inclusive(|&b| b == b'\n')
+        .filter(|line| !is_heading(line))
+        .map(SvnHistoryEntry::try_from)
+        .collect()
+}
+
+/// A streaming iterator over a node's history, youngest first
+#[derive(Debug)]
+pub struct SvnHistoryIter {
+    svnlook: SvnlookCommand,
+    line: Vec<u8>,
+    finished: bool,
+}
+
+impl From<SvnlookCommand> for SvnHistoryIter {
+    fn from(cmd: SvnlookCommand) -> Self {
+        Self {
+            svnlook: cmd,
+            line: vec![],
+            finished: false,
+        }
+    }
+}
+
+impl SvnHistoryIter {
+    fn kill(&mut self) {
+        self.finished = true;
+        let _ = self.svnlook.kill();
+    }
+
+    /// Consume the iterator into a `Vec`, stopping at the first error.
+    pub fn collect_entries(self) -> Result<Vec<SvnHistoryEntry>, SvnError> {
+        collect_or_kill(self, Self::kill)
+    }
+}
+
+impl Drop for SvnHistoryIter {
+    fn drop(&mut self) {
+        let _ = self.svnlook.finish();
+    }
+}
+
+impl Iterator for SvnHistoryIter {
+    type Item = Result<SvnHistoryEntry, SvnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+
+            if self.finished {
+                return None;
+            }
+
+            match self.svnlook.read_until(b'\n', &mut self.line) {
+                Ok(0) => {
+                    self.finished = true;
+                    return match self.svnlook.finish() {
+                        Ok(status) if status.success() => None,
+                        Ok(status) => Some(Err(SvnError::from(status))),
+                        Err(e) => Some(Err(e)),
+                    };
+                }
+                // Skip the REVISION/PATH column headings
+                Ok(_) if is_heading(&self.line) => continue,
+                Ok(_) => return Some(SvnHistoryEntry::try_from(&self.line[..])),
+                Err(e) => return Some(Err(SvnError::from(e))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leading_slash_normalised() {
+        let entry = SvnHistoryEntry::try_from(&b"      12   /trunk/a b\n"[..]).unwrap();
+        assert_eq!(entry.revision, 12);
+        assert_eq!(entry.path, Path::new("trunk/a b"));
+    }
+}
@@ -3,9 +3,11 @@ use std::str::{self, FromStr};
 
 use chrono::{DateTime, FixedOffset};
 
+use super::strip_cr;
 use crate::SvnError;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SvnInfo {
     pub revision: u64,
     pub committer: String,
@@ -13,6 +15,120 @@ pub struct SvnInfo {
     pub message: String,
 }
 
+impl SvnInfo {
+    /// The commit author, or `None` for anonymous commits where svnlook
+    /// reports an empty author.  Any non-empty name is returned as-is, even
+    /// if it is only whitespace.
+    pub fn author(&self) -> Option<&str> {
+        if self.committer.is_empty() {
+            None
+        } else {
+            Some(&self.committer)
+        }
+    }
+
+    /// Whether the commit was made without authentication, leaving
+    /// `svn:author` unset.
+    pub fn is_anonymous(&self) -> bool {
+        self.author().is_none()
+    }
+
+    /// The commit date as an RFC 3339 string, matching its serde
+    /// serialization.  Fractional seconds are included only when present.
+    pub fn date_rfc3339(&self) -> String {
+        self.date.to_rfc3339()
+    }
+}
+
+/// Metadata salvaged from `svnlook info` output, with any field that failed
+/// to parse left as `None` and described in `warnings`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SvnInfoLenient {
+    pub revision: u64,
+    pub committer: Option<String>,
+    pub date: Option<DateTime<FixedOffset>>,
+    pub message: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+fn parse_committer(line: Option<&[u8]>) -> Option<String> {
+    line.map(strip_cr)
+        .map(String::from_utf8_lossy)
+        .map(|c| c.to_string())
+}
+
+fn parse_date(line: Option<&[u8]>) -> Option<DateTime<FixedOffset>> {
+    line.filter(|d| d.len() > 25)
+        .and_then(|d| str::from_utf8(&d[0..25]).ok())
+        .and_then(|d| DateTime::parse_from_str(d, "%Y-%m-%d %H:%M:%S %z").ok())
+}
+
+fn parse_length(line: Option<&[u8]>) -> Option<usize> {
+    line.map(strip_cr)
+        .and_then(|d| str::from_utf8(d).ok())
+        .and_then(|d| usize::from_str(d).ok())
+}
+
+/// Extract the message using its declared byte length, rejecting lengths
+/// which would split a multi-byte UTF-8 character.
+///
+/// The message is never scanned for line breaks, so messages containing
+/// blank lines, numeric lines, or text resembling another info block are
+/// extracted intact.  svnlook terminates the message with a newline, or
+/// CRLF, so a length not followed by one is rejected as inconsistent.
+fn parse_message(rest: Option<&[u8]>, bytes: usize) -> Option<String> {
+    rest.filter(|m| matches!(m.get(bytes), Some(b'\n') | Some(b'\r')))
+        .map(|m| &m[0..bytes])
+        .filter(|m| match str::from_utf8(m) {
+            Err(e) => e.error_len().is_some(),
+            Ok(_) => true,
+        })
+        .map(String::from_utf8_lossy)
+        .map(|m| m.to_string())
+}
+
+impl From<(u64, &[u8])> for SvnInfoLenient {
+    fn from(info: (u64, &[u8])) -> Self {
+        let (revision, bytes) = info;
+        let mut lines = bytes.splitn(4, |b| *b == b'\n');
+        let mut warnings = vec![];
+
+        let committer = parse_committer(lines.next());
+        if committer.is_none() {
+            warnings.push("missing committer".to_string());
+        }
+
+        let date = parse_date(lines.next());
+        if date.is_none() {
+            warnings.push("missing or invalid date".to_string());
+        }
+
+        let length = parse_length(lines.next());
+        let message = match length {
+            Some(length) => parse_message(lines.next(), length),
+            None => {
+                warnings.push("missing or invalid message length".to_string());
+                lines
+                    .next()
+                    .map(|m| strip_cr(m.strip_suffix(b"\n").unwrap_or(m)))
+                    .map(|m| String::from_utf8_lossy(m).to_string())
+            }
+        };
+        if message.is_none() {
+            warnings.push("missing or truncated message".to_string());
+        }
+
+        SvnInfoLenient {
+            revision,
+            committer,
+            date,
+            message,
+            warnings,
+        }
+    }
+}
+
 impl TryFrom<(u64, &[u8])> for SvnInfo {
     type Error = SvnError;
 
@@ -20,32 +136,10 @@ impl TryFrom<(u64, &[u8])> for SvnInfo {
         let (revision, bytes) = info;
         let mut lines = bytes.splitn(4, |b| *b == b'\n');
 
-        let committer = lines
-            .next()
-            .map(String::from_utf8_lossy)
-            .ok_or(SvnError::ParseError)?
-            .to_string();
-
-        let date = lines
-            .next()
-            .filter(|d| d.len() > 25)
-            .and_then(|d| str::from_utf8(&d[0..25]).ok())
-            .and_then(|d| DateTime::parse_from_str(d, "%Y-%m-%d %H:%M:%S %z").ok())
-            .ok_or(SvnError::ParseError)?;
-
-        let bytes = lines
-            .next()
-            .and_then(|d| str::from_utf8(d).ok())
-            .and_then(|d| usize::from_str(d).ok())
-            .ok_or(SvnError::ParseError)?;
-
-        let message = lines
-            .next()
-            .filter(|m| m.len() > bytes)
-            .map(|m| &m[0..bytes])
-            .map(String::from_utf8_lossy)
-            .ok_or(SvnError::ParseError)?
-            .to_string();
+        let committer = parse_committer(lines.next()).ok_or(SvnError::InvalidInfo("committer"))?;
+        let date = parse_date(lines.next()).ok_or(SvnError::InvalidInfo("date"))?;
+        let bytes = parse_length(lines.next()).ok_or(SvnError::InvalidInfo("message length"))?;
+        let message = parse_message(lines.next(), bytes).ok_or(SvnError::InvalidInfo("message"))?;
 
         Ok(SvnInfo {
             revision,
@@ -55,3 +149,63 @@ impl TryFrom<(u64, &[u8])> for SvnInfo {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(bytes: &[u8]) -> Result<SvnInfo, SvnError> {
+        SvnInfo::try_from((1, bytes))
+    }
+
+    #[test]
+    fn adversarial_message() {
+        let message = b"42\n\nbob\n2020-01-02 03:04:05 +0000 (Thu, 02 Jan 2020)\n3\nhi\n\n7";
+        let mut bytes = b"alice\n2020-01-02 03:04:05 +0000 (Thu, 02 Jan 2020)\n".to_vec();
+        bytes.extend_from_slice(format!("{}\n", message.len()).as_bytes());
+        bytes.extend_from_slice(message);
+        bytes.push(b'\n');
+
+        let info = info(&bytes).unwrap();
+        assert_eq!(info.committer, "alice");
+        assert_eq!(info.message.as_bytes(), &message[..]);
+    }
+
+    #[test]
+    fn length_without_terminator() {
+        // A length one short of the message leaves "i" where the newline
+        // should be
+        let result = info(b"alice\n2020-01-02 03:04:05 +0000 (Thu, 02 Jan 2020)\n1\nhi\n");
+
+        match result {
+            Err(SvnError::InvalidInfo("message")) => (),
+            other => panic!("expected an invalid message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn length_splitting_a_character() {
+        // "é" is two bytes, so a length of 2 cuts it in half
+        let result = info(b"alice\n2020-01-02 03:04:05 +0000 (Thu, 02 Jan 2020)\n2\nc\xc3\xa9\n");
+
+        match result {
+            Err(SvnError::InvalidInfo("message")) => (),
+            other => panic!("expected an invalid message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn crlf_matches_lf() {
+        let lf = b"alice\n2020-01-02 03:04:05 +0000 (Thu, 02 Jan 2020)\n5\nhello\n";
+        let crlf = b"alice\r\n2020-01-02 03:04:05 +0000 (Thu, 02 Jan 2020)\r\n5\r\nhello\r\n";
+
+        let expected = info(lf).unwrap();
+        assert_eq!(expected.committer, "alice");
+        assert_eq!(expected.message, "hello");
+        assert_eq!(info(crlf).unwrap(), expected);
+        assert_eq!(
+            SvnInfoLenient::from((1, &crlf[..])).warnings,
+            Vec::<String>::new()
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use chrono::Utc;
+
+use crate::SvnInfo;
+
+/// The longest keyword anchor, from `$` to `$`, which will be expanded
+const MAX_KEYWORD_LEN: usize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keyword {
+    Rev,
+    Author,
+    Date,
+    Url,
+    Id,
+}
+
+impl Keyword {
+    fn from_name(name: &[u8]) -> Option<Self> {
+        Some(match name {
+            b"LastChangedRevision" | b"Revision" | b"Rev" => Keyword::Rev,
+            b"LastChangedBy" | b"Author" => Keyword::Author,
+            b"LastChangedDate" | b"Date" => Keyword::Date,
+            b"HeadURL" | b"URL" => Keyword::Url,
+            b"Id" => Keyword::Id,
+            _ => return None,
+        })
+    }
+}
+
+/// The values substituted for keywords, taken from the revision in which a
+/// file last changed
+#[derive(Debug)]
+pub(crate) struct KeywordValues {
+    keywords: Vec<Keyword>,
+    rev: String,
+    author: String,
+    date: String,
+    url: String,
+    id: String,
+}
+
+impl KeywordValues {
+    /// `keywords` is the value of `svn:keywords`, `info` describes the
+    /// revision the file last changed in, and `url` its full URL.
+    pub(crate) fn new(keywords: &[u8], info: &SvnInfo, url: String) -> Self {
+        let date = info.date.with_timezone(&Utc);
+        let name = url.rsplit('/').next().unwrap_or_default();
+
+        Self {
+            keywords: keywords
+                .split(|b| b.is_ascii_whitespace())
+                .filter_map(Keyword::from_name)
+                .collect(),
+            rev: info.revision.to_string(),
+            author: info.committer.clone(),
+            date: date
+                .format("%Y-%m-%d %H:%M:%S +0000 (%a, %d %b %Y)")
+                .to_string(),
+            id: format!(
+                "{} {} {} {}",
+                name,
+                info.revision,
+                date.format("%Y-%m-%d %H:%M:%SZ"),
+                info.committer
+            ),
+            url,
+        }
+    }
+
+    fn value(&self, keyword: Keyword) -> &str {
+        match keyword {
+            Keyword::Rev => &self.rev,
+            Keyword::Author => &self.author,
+            Keyword::Date => &self.date,
+            Keyword::Url => &self.url,
+            Keyword::Id => &self.id,
+        }
+    }
+
+    /// Expand the keyword anchor at the start of `text`, if there is one,
+    /// returning the expansion and the number of bytes it replaces.
+    fn expand_at(&self, text: &[u8]) -> Option<(Vec<u8>, usize)> {
+        let end = text
+            .iter()
+            .take(MAX_KEYWORD_LEN)
+            .skip(1)
+            .position(|&b| b == b'$' || b == b'\n')
+            .map(|pos| pos + 1)
+            .filter(|&pos| text[pos] == b'$')?;
+
+        let inner = &text[1..end];
+        let name = match inner.iter().position(|&b| b == b':') {
+            // Fixed-width `$Keyword:: ...$` anchors are left untouched
+            Some(pos) if inner.get(pos + 1) == Some(&b':') => return None,
+            Some(pos) if inner.get(pos + 1) == Some(&b' ') => &inner[..pos],
+            Some(_) => return None,
+            None => inner,
+        };
+
+        let keyword = Keyword::from_name(name)?;
+        if !self.keywords.contains(&keyword) {
+            return None;
+        }
+
+        let mut expanded = vec![b'$'];
+        expanded.extend_from_slice(name);
+        expanded.extend_from_slice(b": ");
+        expanded.extend_from_slice(self.value(keyword).as_bytes());
+        expanded.extend_from_slice(b" $");
+
+        Some((expanded, end + 1))
+    }
+
+    /// Expand every enabled keyword anchor in `content`, whether collapsed
+    /// (`$Rev$`) or already expanded (`$Rev: 12 $`).
+    pub(crate) fn expand(&self, content: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(content.len());
+        let mut pos = 0;
+
+        while let Some(offset) = content[pos..].iter().position(|&b| b == b'$') {
+            let start = pos + offset;
+            out.extend_from_slice(&content[pos..start]);
+
+            match self.expand_at(&content[start..]) {
+                Some((expanded, len)) => {
+                    out.extend_from_slice(&expanded);
+                    pos = start + len;
+                }
+                None => {
+                    out.push(b'$');
+                    pos = start + 1;
+                }
+            }
+        }
+
+        out.extend_from_slice(&content[pos..]);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+
+    fn values(keywords: &str) -> KeywordValues {
+        let info = SvnInfo {
+            revision: 12,
+            committer: "alice".to_string(),
+            date: DateTime::parse_from_rfc3339("2020-01-02T03:04:05+01:00").unwrap(),
+            message: String::new(),
+        };
+
+        KeywordValues::new(
+            keywords.as_bytes(),
+            &info,
+            "file:///repo/trunk/a.c".to_string(),
+        )
+    }
+
+    fn expand(keywords: &str, content: &str) -> String {
+        String::from_utf8(values(keywords).expand(content.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn each_keyword() {
+        let all = "Rev Author Date URL Id";
+
+        assert_eq!(expand(all, "$Rev$"), "$Rev: 12 $");
+        assert_eq!(expand(all, "$Author$"), "$Author: alice $");
+        assert_eq!(
+            expand(all, "$Date$"),
+            "$Date: 2020-01-02 02:04:05 +0000 (Thu, 02 Jan 2020) $"
+        );
+        assert_eq!(expand(all, "$URL$"), "$URL: file:///repo/trunk/a.c $");
+        assert_eq!(
+            expand(all, "$Id$"),
+            "$Id: a.c 12 2020-01-02 02:04:05Z alice $"
+        );
+    }
+
+    #[test]
+    fn aliases() {
+        assert_eq!(
+            expand("Revision", "$LastChangedRevision$ $Revision$ $Rev$"),
+            "$LastChangedRevision: 12 $ $Revision: 12 $ $Rev: 12 $"
+        );
+        assert_eq!(expand("LastChangedBy", "$Author$"), "$Author: alice $");
+        assert_eq!(
+            expand("Date", "$LastChangedDate$"),
+            "$LastChangedDate: 2020-01-02 02:04:05 +0000 (Thu, 02 Jan 2020) $"
+        );
+        assert_eq!(expand("HeadURL", "$URL$"), "$URL: file:///repo/trunk/a.c $");
+    }
+
+    #[test]
+    fn only_enabled_keywords() {
+        assert_eq!(
+            expand("Rev", "$Rev$ $Author$ $Unknown$"),
+            "$Rev: 12 $ $Author$ $Unknown$"
+        );
+    }
+
+    #[test]
+    fn already_expanded() {
+        assert_eq!(expand("Rev", "$Rev: 3 $"), "$Rev: 12 $");
+    }
+
+    #[test]
+    fn passthrough() {
+        // Fixed-width anchors, malformed anchors and unterminated anchors are
+        // all left alone
+        assert_eq!(expand("Rev", "$Rev::    $"), "$Rev::    $");
+        assert_eq!(expand("Rev", "$Rev:3$"), "$Rev:3$");
+        assert_eq!(expand("Rev", "$Rev\n$"), "$Rev\n$");
+        assert_eq!(expand("Rev", "costs $5 $Rev$"), "costs $5 $Rev: 12 $");
+    }
+}
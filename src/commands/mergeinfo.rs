@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::SvnError;
+
+/// An inclusive range of merged revisions, as written in `svn:mergeinfo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MergeRange {
+    pub start: u64,
+    pub end: u64,
+    /// Set for ranges marked with `*`, which apply only to the node itself
+    /// and not its children
+    pub non_inheritable: bool,
+}
+
+impl fmt::Display for MergeRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "r{}", self.start)
+        } else {
+            write!(f, "r{}-r{}", self.start, self.end)
+        }
+    }
+}
+
+/// Revisions newly merged into a path from a single source, as returned by
+/// `Repository::merge_summary`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MergeRecord {
+    /// The path whose `svn:mergeinfo` changed
+    pub target: PathBuf,
+    /// The merge source, as a repository path such as `/branches/feature`
+    pub source: String,
+    pub ranges: Vec<MergeRange>,
+}
+
+impl fmt::Display for MergeRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "merged ")?;
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", range)?;
+        }
+        write!(f, " from {}", self.source)
+    }
+}
+
+/// Parsed `svn:mergeinfo`, mapping each source path to its merged ranges
+pub type Mergeinfo = BTreeMap<String, Vec<MergeRange>>;
+
+fn parse_range(range: &str) -> Result<MergeRange, SvnError> {
+    let (range, non_inheritable) = match range.strip_suffix('*') {
+        Some(range) => (range, true),
+        None => (range, false),
+    };
+
+    let (start, end) = match range.find('-') {
+        Some(pos) => (range[..pos].parse()?, range[pos + 1..].parse()?),
+        None => {
+            let revision = range.parse()?;
+            (revision, revision)
+        }
+    };
+
+    if start > end {
+        return Err(SvnError::ParseError);
+    }
+
+    Ok(MergeRange {
+        start,
+        end,
+        non_inheritable,
+    })
+}
+
+/// Parse the value of an `svn:mergeinfo` property: one `SOURCE:RANGES` line
+/// per source, where ranges are comma-separated revisions or inclusive
+/// `N-M` spans, optionally suffixed with `*`.
+pub fn parse_mergeinfo(mergeinfo: &str) -> Result<Mergeinfo, SvnError> {
+    let mut parsed = Mergeinfo::new();
+
+    for line in mergeinfo.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        // Source paths may themselves contain colons, so split on the last
+        let pos = line.rfind(':').ok_or(SvnError::ParseError)?;
+        let ranges = line[pos + 1..]
+            .split(',')
+            .map(str::trim)
+            .filter(|range| !range.is_empty())
+            .map(parse_range)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        parsed
+            .entry(line[..pos].to_string())
+            .or_default()
+            .extend(ranges);
+    }
+
+    Ok(parsed)
+}
+
+/// Remove the revisions covered by `old` from `new`, leaving only ranges
+/// which were newly merged.
+pub(crate) fn subtract_ranges(new: &[MergeRange], old: &[MergeRange]) -> Vec<MergeRange> {
+    let mut remaining = new.to_vec();
+
+    for old in old {
+        remaining = remaining
+            .into_iter()
+            .flat_map(|range| {
+                let mut parts = vec![];
+
+                if old.end < range.start || old.start > range.end {
+                    parts.push(range);
+                    return parts;
+                }
+
+                if range.start < old.start {
+                    parts.push(MergeRange {
+                        end: old.start - 1,
+                        ..range
+                    });
+                }
+
+                if range.end > old.end {
+                    parts.push(MergeRange {
+                        start: old.end + 1,
+                        ..range
+                    });
+                }
+
+                parts
+            })
+            .collect();
+    }
+
+    remaining
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u64, end: u64) -> MergeRange {
+        MergeRange {
+            start,
+            end,
+            non_inheritable: false,
+        }
+    }
+
+    fn ranges(ranges: &[(u64, u64)]) -> Vec<MergeRange> {
+        ranges
+            .iter()
+            .map(|&(start, end)| range(start, end))
+            .collect()
+    }
+
+    #[test]
+    fn parse() {
+        let mergeinfo =
+            parse_mergeinfo("/trunk:1-5,7*, 9\n\n/branches/a:b:4-6\r\n/trunk:12\n").unwrap();
+
+        assert_eq!(mergeinfo.len(), 2);
+        assert_eq!(
+            mergeinfo["/trunk"],
+            [
+                range(1, 5),
+                MergeRange {
+                    non_inheritable: true,
+                    ..range(7, 7)
+                },
+                range(9, 9),
+                range(12, 12),
+            ]
+        );
+        assert_eq!(mergeinfo["/branches/a:b"], [range(4, 6)]);
+    }
+
+    #[test]
+    fn malformed() {
+        for mergeinfo in &[
+            "/trunk",
+            "/trunk:x",
+            "/trunk:1-",
+            "/trunk:-3",
+            "/trunk:5-3",
+            "/trunk:*",
+            "/trunk:1-2-3",
+        ] {
+            assert!(parse_mergeinfo(mergeinfo).is_err(), "{}", mergeinfo);
+        }
+    }
+
+    #[test]
+    fn subtract() {
+        let new = ranges(&[(3, 8)]);
+
+        assert_eq!(subtract_ranges(&new, &[]), new);
+        assert_eq!(subtract_ranges(&new, &ranges(&[(1, 2), (9, 10)])), new);
+        assert_eq!(subtract_ranges(&new, &ranges(&[(1, 4)])), ranges(&[(5, 8)]));
+        assert_eq!(subtract_ranges(&new, &ranges(&[(7, 9)])), ranges(&[(3, 6)]));
+        assert_eq!(
+            subtract_ranges(&new, &ranges(&[(5, 6)])),
+            ranges(&[(3, 4), (7, 8)])
+        );
+        assert_eq!(
+            subtract_ranges(&new, &ranges(&[(3, 3), (5, 5), (8, 8)])),
+            ranges(&[(4, 4), (6, 7)])
+        );
+        assert_eq!(subtract_ranges(&new, &ranges(&[(3, 8)])), []);
+        assert_eq!(subtract_ranges(&new, &ranges(&[(1, 10)])), []);
+        assert_eq!(
+            subtract_ranges(&ranges(&[(1, 2), (5, 6)]), &ranges(&[(2, 5)])),
+            ranges(&[(1, 1), (6, 6)])
+        );
+    }
+
+    #[test]
+    fn subtract_keeps_non_inheritable() {
+        let new = [MergeRange {
+            non_inheritable: true,
+            ..range(1, 4)
+        }];
+
+        assert_eq!(
+            subtract_ranges(&new, &ranges(&[(2, 2)])),
+            [
+                MergeRange {
+                    non_inheritable: true,
+                    ..range(1, 1)
+                },
+                MergeRange {
+                    non_inheritable: true,
+                    ..range(3, 4)
+                },
+            ]
+        );
+    }
+}
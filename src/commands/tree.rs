@@ -0,0 +1,207 @@
+use std::convert::TryFrom;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{collect_or_kill, try_chomp};
+use crate::{Revision, SvnError, SvnlookCommand};
+
+/// A single node listed by `svnlook tree`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SvnTreeEntry {
+    /// The path relative to the repository root, without a leading slash.
+    /// The root itself has an empty path.
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// The node-revision ID, if requested with `SvnTreeBuilder::show_ids`
+    pub node_id: Option<String>,
+}
+
+impl SvnTreeEntry {
+    /// Parse a line of `--show-ids` output, with the layout `<path> <<id>>`.
+    fn with_id(line: &[u8]) -> Result<Self, SvnError> {
+        let line = try_chomp(line)?;
+        let pos = line
+            .windows(2)
+            .rposition(|w| w == b" <")
+            .filter(|_| line.ends_with(b">"))
+            .ok_or(SvnError::ParseError)?;
+
+        let mut path = line[..pos].to_vec();
+        path.push(b'\n');
+
+        Ok(SvnTreeEntry {
+            node_id: Some(String::from_utf8_lossy(&line[pos + 2..line.len() - 1]).to_string()),
+            ..SvnTreeEntry::try_from(&path[..])?
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for SvnTreeEntry {
+    type Error = SvnError;
+
+    fn try_from(line: &[u8]) -> Result<Self, Self::Error> {
+        let line = try_chomp(line)?;
+
+        if line.is_empty() {
+            return Err(SvnError::ParseError);
+        }
+
+        // svnlook lists the root as `/`, and other full paths with or
+        // without a leading slash depending on how the tree was requested
+        let is_dir = line.ends_with(b"/");
+        let path = line.strip_prefix(b"/").unwrap_or(line);
+        let path = path.strip_suffix(b"/").unwrap_or(path);
+
+        Ok(SvnTreeEntry {
+            path: PathBuf::from(String::from_utf8_lossy(path).to_string()),
+            is_dir,
+            node_id: None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct SvnTreeBuilder {
+    repository: PathBuf,
+    command: Command,
+    path: Option<PathBuf>,
+    show_ids: bool,
+}
+
+impl SvnTreeBuilder {
+    pub(crate) fn new(repository: &Path, mut command: Command) -> Self {
+        command.args(["tree", "--full-paths"]);
+
+        Self {
+            repository: repository.to_path_buf(),
+            command,
+            path: None,
+            show_ids: false,
+        }
+    }
+
+    /// Include each node's node-revision ID, for auditing structural sharing.
+    pub fn show_ids(&mut self) -> &mut Self {
+        self.command.arg("--show-ids");
+        self.show_ids = true;
+        self
+    }
+
+    /// List only the starting directory and its immediate children.
+    pub fn non_recursive(&mut self) -> &mut Self {
+        self.command.arg("--non-recursive");
+        self
+    }
+
+    pub fn revision<R: Into<Revision>>(&mut self, revision: R) -> &mut Self {
+        self.command.arg(format!("-r{}", revision.into()));
+        self
+    }
+
+    /// List only the subtree rooted at `path` rather than the whole repository.
+    pub fn path<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn spawn(&mut self) -> Result<SvnTreeIter, SvnError> {
+        self.command.arg("--");
+        self.command.arg(&self.repository);
+        if let Some(path) = &self.path {
+            self.command.arg(path);
+        }
+        let mut iter = SvnTreeIter::from(SvnlookCommand::spawn(&mut self.command)?);
+        iter.show_ids = self.show_ids;
+        Ok(iter)
+    }
+}
+
+/// A streaming iterator over the nodes of a tree, in svnlook's depth-first order
+#[derive(Debug)]
+pub struct SvnTreeIter {
+    svnlook: SvnlookCommand,
+    line: Vec<u8>,
+    finished: bool,
+    show_ids: bool,
+}
+
+impl From<SvnlookCommand> for SvnTreeIter {
+    fn from(cmd: SvnlookCommand) -> Self {
+        Self {
+            svnlook: cmd,
+            line: vec![],
+            finished: false,
+            show_ids: false,
+        }
+    }
+}
+
+impl SvnTreeIter {
+    fn kill(&mut self) {
+        self.finished = true;
+        let _ = self.svnlook.kill();
+    }
+
+    /// Consume the iterator into a `Vec`, stopping at the first error.
+    pub fn collect_entries(self) -> Result<Vec<SvnTreeEntry>, SvnError> {
+        collect_or_kill(self, Self::kill)
+    }
+}
+
+impl Drop for SvnTreeIter {
+    fn drop(&mut self) {
+        let _ = self.svnlook.finish();
+    }
+}
+
+impl Iterator for SvnTreeIter {
+    type Item = Result<SvnTreeEntry, SvnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line.clear();
+
+        if self.finished {
+            return None;
+        }
+
+        match self.svnlook.read_until(b'\n', &mut self.line) {
+            Ok(0) => {
+                self.finished = true;
+                match self.svnlook.finish() {
+                    Ok(status) if status.success() => None,
+                    Ok(status) => Some(Err(SvnError::from(status))),
+                    Err(e) => Some(Err(e)),
+                }
+            }
+            Ok(_) if self.show_ids => Some(SvnTreeEntry::with_id(&self.line[..])),
+            Ok(_) => Some(SvnTreeEntry::try_from(&self.line[..])),
+            Err(e) => Some(Err(SvnError::from(e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leading_slash_normalised() {
+        for (line, path, is_dir) in &[
+            (&b"/\n"[..], "", true),
+            (b"trunk/\n", "trunk", true),
+            (b"/trunk/\n", "trunk", true),
+            (b"trunk/f\n", "trunk/f", false),
+            (b"/trunk/f\n", "trunk/f", false),
+        ] {
+            let entry = SvnTreeEntry::try_from(*line).unwrap();
+            assert_eq!(entry.path, Path::new(path));
+            assert_eq!(entry.is_dir, *is_dir);
+        }
+
+        let entry = SvnTreeEntry::with_id(b"/trunk/f <1-2.0.r3/4>\n").unwrap();
+        assert_eq!(entry.path, Path::new("trunk/f"));
+        assert_eq!(entry.node_id.as_deref(), Some("1-2.0.r3/4"));
+    }
+}
@@ -0,0 +1,151 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::{Repository, SvnChange, SvnError, SvnFrom, SvnInfo, SvnStatus};
+
+/// The metadata and changed paths of a single revision
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SvnRevisionSummary {
+    pub info: SvnInfo,
+    pub changes: Vec<SvnChange>,
+}
+
+/// A single path change with its commit context, as returned by
+/// `Repository::change_events`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChangeEvent {
+    pub revision: u64,
+    pub author: Option<String>,
+    pub date: DateTime<FixedOffset>,
+    pub change: SvnChange,
+}
+
+/// A single changed path within a `RevisionRecord`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RecordChange {
+    /// A short status label, as returned by `SvnStatus::label`
+    pub status: &'static str,
+    pub path: PathBuf,
+    pub copy_from: Option<SvnFrom>,
+}
+
+/// A compact per-revision record, as returned by `Repository::revision_record`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RevisionRecord {
+    pub revision: u64,
+    pub author: Option<String>,
+    pub date: DateTime<FixedOffset>,
+    pub message: String,
+    pub changes: Vec<RecordChange>,
+}
+
+impl From<SvnRevisionSummary> for RevisionRecord {
+    fn from(summary: SvnRevisionSummary) -> Self {
+        let SvnRevisionSummary { info, changes } = summary;
+
+        RevisionRecord {
+            revision: info.revision,
+            author: info.author().map(String::from),
+            date: info.date,
+            message: info.message,
+            changes: changes
+                .into_iter()
+                .map(|change| RecordChange {
+                    status: change.display_label(),
+                    copy_from: match change.status {
+                        SvnStatus::Copied(from) | SvnStatus::Replaced(Some(from)) => Some(from),
+                        _ => None,
+                    },
+                    path: change.path,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// An iterator over revision summaries, as returned by `Repository::walk`
+#[derive(Debug)]
+pub struct SvnWalk {
+    repository: Repository,
+    revisions: Range<u64>,
+}
+
+impl SvnWalk {
+    pub(crate) fn new(repository: Repository, revisions: Range<u64>) -> Self {
+        Self {
+            repository,
+            revisions,
+        }
+    }
+
+    fn summary(&self, revision: u64) -> Result<SvnRevisionSummary, SvnError> {
+        Ok(SvnRevisionSummary {
+            info: self.repository.info(revision)?,
+            changes: self.repository.changed(revision)?.collect_changes()?,
+        })
+    }
+}
+
+impl Iterator for SvnWalk {
+    type Item = Result<SvnRevisionSummary, SvnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.revisions.next().map(|revision| self.summary(revision))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.revisions.size_hint()
+    }
+}
+
+/// An iterator over the metadata of revisions touching a path, as returned by
+/// `Repository::path_log`
+#[derive(Debug)]
+pub struct SvnPathLog {
+    repository: Repository,
+    revisions: Range<u64>,
+    path: PathBuf,
+}
+
+impl SvnPathLog {
+    pub(crate) fn new(repository: Repository, revisions: Range<u64>, path: PathBuf) -> Self {
+        Self {
+            repository,
+            revisions,
+            path,
+        }
+    }
+
+    fn touches(&self, revision: u64) -> Result<bool, SvnError> {
+        let mut changed = self.repository.changed(revision)?.under(self.path.clone());
+        let found = changed.next().transpose()?.is_some();
+        changed.kill();
+        Ok(found)
+    }
+}
+
+impl Iterator for SvnPathLog {
+    type Item = Result<SvnInfo, SvnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(revision) = self.revisions.next() {
+            match self.touches(revision) {
+                Ok(true) => return Some(self.repository.info(revision)),
+                Ok(false) => (),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.revisions.size_hint().1)
+    }
+}
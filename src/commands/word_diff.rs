@@ -0,0 +1,264 @@
+use crate::{DiffLineKind, Hunk};
+
+/// Lines whose token counts multiply past this are marked wholly changed
+/// rather than compared token by token.
+const MAX_CELLS: usize = 1 << 20;
+
+/// Whether a segment of a line is shared with its counterpart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SegmentKind {
+    Equal,
+    Changed,
+}
+
+/// A run of bytes within a changed line
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WordSegment {
+    pub kind: SegmentKind,
+    pub text: Vec<u8>,
+}
+
+/// Intra-line differences between a removed line and the added line that
+/// replaced it, as returned by `Hunk::word_diffs`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WordDiff {
+    /// Index of the removed line within `Hunk::lines`
+    pub old_line: usize,
+    /// Index of the added line within `Hunk::lines`
+    pub new_line: usize,
+    pub old: Vec<WordSegment>,
+    pub new: Vec<WordSegment>,
+}
+
+/// Split a line into runs of word characters, runs of whitespace, and
+/// individual punctuation bytes.
+fn tokenize(line: &[u8]) -> Vec<&[u8]> {
+    let class = |b: u8| {
+        if b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80 {
+            0
+        } else if b.is_ascii_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+
+    let mut tokens = vec![];
+    let mut start = 0;
+
+    for i in 1..=line.len() {
+        if i == line.len() || class(line[i]) != class(line[start]) || class(line[start]) == 2 {
+            tokens.push(&line[start..i]);
+            start = i;
+        }
+    }
+
+    tokens
+}
+
+fn push_segment(segments: &mut Vec<WordSegment>, kind: SegmentKind, text: &[u8]) {
+    match segments.last_mut() {
+        Some(last) if last.kind == kind => last.text.extend_from_slice(text),
+        _ => segments.push(WordSegment {
+            kind,
+            text: text.to_vec(),
+        }),
+    }
+}
+
+/// Compare two lines by the longest common subsequence of their tokens.
+fn diff_words(old: &[u8], new: &[u8]) -> (Vec<WordSegment>, Vec<WordSegment>) {
+    let (a, b) = (tokenize(old), tokenize(new));
+    let (mut old_segments, mut new_segments) = (vec![], vec![]);
+
+    if a.len().saturating_mul(b.len()) > MAX_CELLS {
+        push_segment(&mut old_segments, SegmentKind::Changed, old);
+        push_segment(&mut new_segments, SegmentKind::Changed, new);
+        return (old_segments, new_segments);
+    }
+
+    // lcs[i][j] is the LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            push_segment(&mut old_segments, SegmentKind::Equal, a[i]);
+            push_segment(&mut new_segments, SegmentKind::Equal, b[j]);
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push_segment(&mut old_segments, SegmentKind::Changed, a[i]);
+            i += 1;
+        } else {
+            push_segment(&mut new_segments, SegmentKind::Changed, b[j]);
+            j += 1;
+        }
+    }
+
+    (old_segments, new_segments)
+}
+
+impl Hunk {
+    /// Compute intra-line differences for changed lines.  Each run of removed
+    /// lines immediately followed by added lines is paired up in order, and
+    /// any surplus lines on either side are left unpaired.
+    pub fn word_diffs(&self) -> Vec<WordDiff> {
+        let mut diffs = vec![];
+        let mut i = 0;
+
+        while i < self.lines.len() {
+            let removed_start = i;
+            while i < self.lines.len() && self.lines[i].kind == DiffLineKind::Removed {
+                i += 1;
+            }
+            let added_start = i;
+            while i < self.lines.len() && self.lines[i].kind == DiffLineKind::Added {
+                i += 1;
+            }
+
+            if i == removed_start {
+                i += 1;
+                continue;
+            }
+
+            for (old_line, new_line) in (removed_start..added_start).zip(added_start..i) {
+                let (old, new) =
+                    diff_words(&self.lines[old_line].content, &self.lines[new_line].content);
+                diffs.push(WordDiff {
+                    old_line,
+                    new_line,
+                    old,
+                    new,
+                });
+            }
+        }
+
+        diffs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiffLine;
+    use SegmentKind::{Changed, Equal};
+
+    fn hunk(lines: &[&str]) -> Hunk {
+        let lines = lines
+            .iter()
+            .map(|line| DiffLine {
+                kind: match line.as_bytes()[0] {
+                    b'+' => DiffLineKind::Added,
+                    b'-' => DiffLineKind::Removed,
+                    _ => DiffLineKind::Context,
+                },
+                content: line.as_bytes()[1..].to_vec(),
+                missing_newline: false,
+            })
+            .collect();
+
+        Hunk {
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines,
+        }
+    }
+
+    fn segments(segments: &[(SegmentKind, &str)]) -> Vec<WordSegment> {
+        segments
+            .iter()
+            .map(|(kind, text)| WordSegment {
+                kind: *kind,
+                text: text.as_bytes().to_vec(),
+            })
+            .collect()
+    }
+
+    fn pairs(hunk: &Hunk) -> Vec<(usize, usize)> {
+        hunk.word_diffs()
+            .iter()
+            .map(|diff| (diff.old_line, diff.new_line))
+            .collect()
+    }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            tokenize(b"foo_1  bar(x)"),
+            [&b"foo_1"[..], b"  ", b"bar", b"(", b"x", b")"]
+        );
+        assert!(tokenize(b"").is_empty());
+    }
+
+    #[test]
+    fn equal_counts_pair_in_order() {
+        let hunk = hunk(&["-let a = b;", "-x", "+let a = c;", "+y"]);
+        let diffs = hunk.word_diffs();
+
+        assert_eq!(pairs(&hunk), [(0, 2), (1, 3)]);
+        assert_eq!(
+            diffs[0].old,
+            segments(&[(Equal, "let a = "), (Changed, "b"), (Equal, ";")])
+        );
+        assert_eq!(
+            diffs[0].new,
+            segments(&[(Equal, "let a = "), (Changed, "c"), (Equal, ";")])
+        );
+        assert_eq!(diffs[1].old, segments(&[(Changed, "x")]));
+        assert_eq!(diffs[1].new, segments(&[(Changed, "y")]));
+    }
+
+    #[test]
+    fn surplus_lines_are_unpaired() {
+        assert_eq!(pairs(&hunk(&["-a", "-b", "-c", "+a"])), [(0, 3)]);
+        assert_eq!(pairs(&hunk(&[" x", "-a", "+a", "+b", "+c"])), [(1, 2)]);
+        assert_eq!(pairs(&hunk(&["-a", " x", "+b"])), []);
+        assert_eq!(
+            pairs(&hunk(&["-a", "+b", " x", "-c", "+d"])),
+            [(0, 1), (3, 4)]
+        );
+    }
+
+    #[test]
+    fn whitespace_only_changes() {
+        let diffs = hunk(&["-a  b", "+a\tb"]).word_diffs();
+
+        assert_eq!(
+            diffs[0].old,
+            segments(&[(Equal, "a"), (Changed, "  "), (Equal, "b")])
+        );
+        assert_eq!(
+            diffs[0].new,
+            segments(&[(Equal, "a"), (Changed, "\t"), (Equal, "b")])
+        );
+    }
+
+    #[test]
+    fn long_lines_change_whole() {
+        let old = "x ".repeat(513);
+        let new = format!("{}y", old);
+        assert!(tokenize(old.as_bytes()).len() * tokenize(new.as_bytes()).len() > MAX_CELLS);
+
+        let diffs = hunk(&[&format!("-{}", old), &format!("+{}", new)]).word_diffs();
+        assert_eq!(diffs[0].old, segments(&[(Changed, &old)]));
+        assert_eq!(diffs[0].new, segments(&[(Changed, &new)]));
+
+        let diffs = hunk(&["-x x x ", "+x x x y"]).word_diffs();
+        assert_eq!(diffs[0].new, segments(&[(Equal, "x x x "), (Changed, "y")]));
+    }
+}
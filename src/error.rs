@@ -1,23 +1,81 @@
+use std::path::PathBuf;
+use std::process::ExitStatus;
 use std::{error::Error, fmt, io};
 
+use crate::child_reader::ExitError;
+
 #[derive(Debug)]
 pub enum SvnError {
     CommandError(io::Error),
-    ExitFailure(std::process::ExitStatus),
+    /// The svnlook binary could not be found
+    BinaryNotFound(PathBuf),
+    ExitFailure(ExitStatus),
+    /// The command was killed by the given signal
+    Signalled(i32),
+    EmptyOutput,
+    WorkingCopy(PathBuf),
+    NotRepository(PathBuf),
+    NotFile(PathBuf),
+    /// The path does not exist at the requested revision
+    PathNotFound(PathBuf),
     ParseError,
+    /// `svnlook info` output was malformed, naming the field which failed
+    InvalidInfo(&'static str),
+    /// A line of `svnlook changed` output was not a well-formed change
+    MalformedChange(String),
+    Utf8(std::str::Utf8Error),
+    SizeMismatch(u64, u64),
+    #[cfg(feature = "glob")]
+    PatternError(globset::Error),
+    #[cfg(feature = "json")]
+    JsonError(serde_json::Error),
 }
 
 impl Error for SvnError {}
 
 impl From<io::Error> for SvnError {
     fn from(err: io::Error) -> Self {
+        // Streaming readers report a failed exit as an io::Error
+        if let Some(ExitError(status)) = err.get_ref().and_then(|e| e.downcast_ref()) {
+            return SvnError::from(*status);
+        }
+
         SvnError::CommandError(err)
     }
 }
 
+impl From<ExitStatus> for SvnError {
+    fn from(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            if let Some(signal) = status.signal() {
+                return SvnError::Signalled(signal);
+            }
+        }
+
+        SvnError::ExitFailure(status)
+    }
+}
+
+#[cfg(feature = "glob")]
+impl From<globset::Error> for SvnError {
+    fn from(err: globset::Error) -> Self {
+        SvnError::PatternError(err)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for SvnError {
+    fn from(err: serde_json::Error) -> Self {
+        SvnError::JsonError(err)
+    }
+}
+
 impl From<std::str::Utf8Error> for SvnError {
-    fn from(_err: std::str::Utf8Error) -> Self {
-        SvnError::ParseError
+    fn from(err: std::str::Utf8Error) -> Self {
+        SvnError::Utf8(err)
     }
 }
 
@@ -31,8 +89,35 @@ impl fmt::Display for SvnError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             SvnError::CommandError(io) => io.fmt(f),
+            SvnError::BinaryNotFound(path) => write!(f, "{} not found", path.display()),
             SvnError::ExitFailure(status) => write!(f, "non-zero exit from command: {}", status),
+            SvnError::Signalled(signal) => write!(f, "command killed by signal {}", signal),
+            SvnError::EmptyOutput => write!(f, "no output from command"),
+            SvnError::WorkingCopy(path) => write!(
+                f,
+                "{} looks like a working copy, not a repository",
+                path.display()
+            ),
+            SvnError::NotRepository(path) => {
+                write!(f, "{} is not a repository", path.display())
+            }
+            SvnError::NotFile(path) => write!(f, "{} is not a file", path.display()),
+            SvnError::PathNotFound(path) => write!(f, "{} does not exist", path.display()),
             SvnError::ParseError => write!(f, "parse error"),
+            SvnError::InvalidInfo(field) => write!(f, "invalid {} in svnlook info", field),
+            SvnError::MalformedChange(line) => write!(f, "malformed change line: {:?}", line),
+            SvnError::Utf8(err) => err.fmt(f),
+            SvnError::SizeMismatch(expected, actual) => {
+                write!(
+                    f,
+                    "size mismatch: expected {} bytes, read {}",
+                    expected, actual
+                )
+            }
+            #[cfg(feature = "glob")]
+            SvnError::PatternError(err) => err.fmt(f),
+            #[cfg(feature = "json")]
+            SvnError::JsonError(err) => err.fmt(f),
         }
     }
 }
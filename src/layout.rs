@@ -0,0 +1,28 @@
+use std::fs;
+use std::path::Path;
+
+/// The presence of a repository's standard files and directories, as
+/// returned by `Repository::layout`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RepoLayout {
+    pub hooks: bool,
+    pub conf: bool,
+    pub db: bool,
+    /// The repository format number from the top-level `format` file, if it
+    /// exists and is readable
+    pub format: Option<u32>,
+}
+
+impl RepoLayout {
+    pub(crate) fn inspect(path: &Path) -> Self {
+        Self {
+            hooks: path.join("hooks").is_dir(),
+            conf: path.join("conf").is_dir(),
+            db: path.join("db").is_dir(),
+            format: fs::read_to_string(path.join("format"))
+                .ok()
+                .and_then(|format| format.lines().next()?.trim().parse().ok()),
+        }
+    }
+}
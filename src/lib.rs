@@ -1,22 +1,77 @@
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::fs;
 use std::io::{self, BufRead, BufReader, Read};
+use std::ops::{Bound, ControlFlow, Range, RangeBounds};
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
 use std::str;
+use std::thread;
+use std::time::Duration;
 
+use chrono::{DateTime, FixedOffset};
+
+#[cfg(feature = "cache")]
+mod cache;
 mod commands;
 mod child_reader;
+#[cfg(feature = "color")]
+mod color;
 mod error;
+mod layout;
+mod parallel;
+#[cfg(feature = "glob")]
+mod policy;
+pub mod prelude;
+mod repository_at;
+mod revision;
+mod subtree;
+#[cfg(all(test, unix))]
+mod testing;
+mod transaction;
 
+#[cfg(feature = "cache")]
+pub use cache::*;
 pub use commands::*;
+#[cfg(feature = "color")]
+pub use color::*;
 pub use error::*;
+pub use layout::*;
+#[cfg(feature = "glob")]
+pub use policy::*;
+pub use repository_at::*;
+pub use revision::*;
+pub use subtree::*;
+pub use transaction::*;
 
 use child_reader::ChildReader;
 
+/// Whether svnlook's stderr reports the given Subversion error code, such as
+/// `E160013` for a path missing from the requested revision.
+fn reports_error(stderr: &[u8], code: &str) -> bool {
+    let code = code.as_bytes();
+
+    stderr
+        .windows(code.len() + 1)
+        .any(|w| w.starts_with(code) && w.ends_with(b":"))
+}
+
+/// The most `svnlook filesize` processes `Repository::changed_with_sizes`
+/// runs at once
+pub const SIZE_PROBE_CONCURRENCY: usize = 8;
+
+/// The contents of a file before and after a revision, as returned by
+/// `Repository::file_versions`
+pub type FileVersions = (Option<Vec<u8>>, Option<Vec<u8>>);
+
 /// A struct representing the path to an svnlook binary
 #[derive(Default, Debug, Clone)]
 pub struct Svnlook {
     pub path: Option<PathBuf>,
+    /// A program and arguments to run svnlook through, such as
+    /// `["sudo", "-u", "svn"]`
+    pub prefix: Vec<OsString>,
 }
 
 /// An interface to an SVN repository using a given svnlook command
@@ -24,8 +79,32 @@ pub struct Svnlook {
 pub struct Repository {
     svnlook: Svnlook,
     pub path: PathBuf,
+    #[cfg(feature = "cache")]
+    cache: Option<CatCache>,
 }
 
+/// A running svnlook command, streaming its output via `Read` and `BufRead`
+///
+/// Standard `Read` adaptors compose as usual.  Use `by_ref()` to apply one
+/// without giving up the command, for example to read a prefix:
+///
+/// ```no_run
+/// # fn main() -> Result<(), svnlook::SvnError> {
+/// use std::io::Read;
+///
+/// let repo = svnlook::Repository::from("/path/to/repo");
+/// let mut cat = repo.cat(1, "trunk/README")?;
+///
+/// let mut prefix = vec![];
+/// cat.by_ref().take(1024).read_to_end(&mut prefix)?;
+///
+/// // The rest of the file remains available.
+/// std::io::copy(&mut cat, &mut std::io::sink())?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Dropping the command early closes the pipe and reaps the child.
 #[derive(Debug)]
 pub struct SvnlookCommand {
     child: BufReader<ChildReader>,
@@ -40,9 +119,16 @@ impl SvnlookCommand {
         })
     }
 
+    /// Close the pipe and wait for svnlook to exit, returning its status.
+    /// This is the only public method; everything else is via `Read` and
+    /// `BufRead`.
     pub fn finish(&mut self) -> Result<ExitStatus, SvnError> {
         Ok(self.child.get_mut().finish()?)
     }
+
+    pub(crate) fn kill(&mut self) -> Result<ExitStatus, SvnError> {
+        Ok(self.child.get_mut().kill()?)
+    }
 }
 
 impl Read for SvnlookCommand {
@@ -69,23 +155,86 @@ impl<P: Into<PathBuf>> From<P> for Svnlook {
     fn from(path: P) -> Self {
         Self {
             path: Some(path.into()),
+            prefix: vec![],
         }
     }
 }
 
 impl Svnlook {
     fn command(&self) -> Command {
-        Command::new(
-            self.path
-                .as_ref()
-                .map(|path| path.as_path())
-                .unwrap_or(Path::new("svnlook")),
-        )
+        let svnlook = self.path.as_deref().unwrap_or_else(|| Path::new("svnlook"));
+
+        match self.prefix.split_first() {
+            Some((program, args)) => {
+                let mut cmd = Command::new(program);
+                cmd.args(args).arg(svnlook);
+                cmd
+            }
+            None => Command::new(svnlook),
+        }
+    }
+
+    /// Run svnlook via another program, such as `sudo` or an ssh wrapper.  The
+    /// first item is the program, and svnlook and its arguments are appended
+    /// to the rest.
+    pub fn with_prefix<I, S>(mut self, prefix: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.prefix = prefix.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Check that svnlook can be run, returning its version, such as
+    /// `"1.14.2"`.
+    pub fn probe(&self) -> Result<String, SvnError> {
+        let n = match self.command().args(["--version", "--quiet"]).output() {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let svnlook = self.path.as_deref().unwrap_or_else(|| Path::new("svnlook"));
+                return Err(SvnError::BinaryNotFound(svnlook.to_path_buf()));
+            }
+            n => n?,
+        };
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        let version = str::from_utf8(&n.stdout[..])?.trim();
+
+        if version.is_empty() {
+            return Err(SvnError::EmptyOutput);
+        }
+
+        Ok(version.to_string())
     }
 
     pub fn repository<P: Into<PathBuf>>(&self, path: P) -> Repository {
         Repository::new_with_svnlook(path, self.clone())
     }
+
+    /// Find the repositories immediately beneath `parent`, such as those
+    /// hosted under `/srv/svn`, sorted by path.  Subdirectories which fail
+    /// `Repository::validate` are skipped.
+    pub fn discover<P: AsRef<Path>>(&self, parent: P) -> Result<Vec<Repository>, SvnError> {
+        let mut repositories = vec![];
+
+        for entry in fs::read_dir(parent)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            let repository = self.repository(path);
+            if repository.validate().is_ok() {
+                repositories.push(repository);
+            }
+        }
+
+        repositories.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(repositories)
+    }
 }
 
 impl<P: Into<PathBuf>> From<P> for Repository {
@@ -93,6 +242,8 @@ impl<P: Into<PathBuf>> From<P> for Repository {
         Self {
             svnlook: Svnlook::default(),
             path: path.into(),
+            #[cfg(feature = "cache")]
+            cache: None,
         }
     }
 }
@@ -106,7 +257,48 @@ impl Repository {
         Self {
             svnlook,
             path: path.into(),
+            #[cfg(feature = "cache")]
+            cache: None,
+        }
+    }
+
+    /// Consult and populate `cache` in `cat_to_vec`.
+    #[cfg(feature = "cache")]
+    pub fn with_cache(mut self, cache: CatCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Check the path looks like a repository, catching the common mistake of
+    /// passing a working copy, which svnlook rejects cryptically.
+    pub fn validate(&self) -> Result<(), SvnError> {
+        if self.path.join(".svn").is_dir() {
+            return Err(SvnError::WorkingCopy(self.path.clone()));
         }
+
+        if !self.path.join("format").is_file() || !self.path.join("db").is_dir() {
+            return Err(SvnError::NotRepository(self.path.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Report which of the standard repository files and directories exist.
+    /// This inspects the filesystem directly rather than running svnlook.
+    pub fn layout(&self) -> RepoLayout {
+        RepoLayout::inspect(&self.path)
+    }
+
+    /// Pin a handle to a single revision, for workflows making many calls
+    /// against it.
+    pub fn at<R: Into<Revision>>(&self, revision: R) -> RepositoryAt {
+        RepositoryAt::new(self.clone(), revision.into())
+    }
+
+    /// Scope a handle to the subtree at `root`, for tools which only ever
+    /// work within it.
+    pub fn subtree<P: Into<PathBuf>>(&self, root: P) -> SubtreeRepository {
+        SubtreeRepository::new(self.clone(), root.into())
     }
 
     pub fn youngest(&self) -> Result<u64, SvnError> {
@@ -119,16 +311,27 @@ impl Repository {
             .output()?;
 
         if !n.status.success() {
-            return Err(SvnError::ExitFailure(n.status));
+            return Err(SvnError::from(n.status));
         }
 
-        str::from_utf8(&n.stdout[..])?
-            .trim()
-            .parse()
-            .map_err(SvnError::from)
+        let youngest = str::from_utf8(&n.stdout[..])?.trim();
+
+        // A new repository reports 0, so no output indicates a broken svnlook
+        if youngest.is_empty() {
+            return Err(SvnError::EmptyOutput);
+        }
+
+        youngest.parse().map_err(SvnError::from)
+    }
+
+    /// Check whether a revision exists, by comparing it against the youngest
+    /// revision.  Revision 0 always exists, even in an empty repository.
+    pub fn revision_exists<R: Into<Revision>>(&self, revision: R) -> Result<bool, SvnError> {
+        Ok(revision.into().0 <= self.youngest()?)
     }
 
-    pub fn info(&self, revision: u64) -> Result<SvnInfo, SvnError> {
+    pub fn info<R: Into<Revision>>(&self, revision: R) -> Result<SvnInfo, SvnError> {
+        let revision = revision.into();
         let n = self
             .svnlook
             .command()
@@ -140,39 +343,1469 @@ impl Repository {
             .output()?;
 
         if !n.status.success() {
-            return Err(SvnError::ExitFailure(n.status));
+            return Err(SvnError::from(n.status));
         }
 
-        SvnInfo::try_from((revision, &n.stdout[..]))
+        SvnInfo::try_from((revision.0, &n.stdout[..]))
     }
 
-    pub fn changed(&self, revision: u64) -> Result<SvnChangedIter, SvnError> {
-        let mut cmd = self.svnlook.command();
-        cmd.args(&["changed", "--copy-info", "-r"])
+    /// As `info`, but salvage whichever fields parse successfully rather than
+    /// failing outright on unusual output.
+    pub fn info_lenient<R: Into<Revision>>(&self, revision: R) -> Result<SvnInfoLenient, SvnError> {
+        let revision = revision.into();
+        let n = self
+            .svnlook
+            .command()
+            .arg("info")
+            .arg("-r")
             .arg(revision.to_string())
             .arg("--")
+            .arg(&self.path)
+            .output()?;
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        Ok(SvnInfoLenient::from((revision.0, &n.stdout[..])))
+    }
+
+    pub fn changed<R: Into<Revision>>(&self, revision: R) -> Result<SvnChangedIter, SvnError> {
+        let mut cmd = self.svnlook.command();
+        cmd.args(["changed", "--copy-info", "-r"])
+            .arg(revision.into().to_string())
+            .arg("--")
             .arg(&self.path);
 
         Ok(SvnChangedIter::from(SvnlookCommand::spawn(&mut cmd)?))
     }
 
+    /// A window of a revision's changes, for paging through very large
+    /// revisions.  svnlook has no pagination of its own, so the first `offset`
+    /// changes are parsed and discarded, and svnlook is killed once the window
+    /// is read rather than left to list the rest.
+    ///
+    /// svnlook's exit status is only checked if the window reaches the end of
+    /// the list.
+    pub fn changed_page<R: Into<Revision>>(
+        &self,
+        revision: R,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<SvnChange>, SvnError> {
+        let mut changed = self.changed(revision)?;
+        let mut page = vec![];
+
+        // Skipped entries are still checked, so a parse error before the
+        // page is reported rather than silently dropped
+        for _ in 0..offset {
+            match changed.next() {
+                Some(change) => drop(change?),
+                None => return Ok(page),
+            }
+        }
+
+        for change in changed.by_ref().take(limit) {
+            page.push(change?);
+        }
+
+        changed.kill();
+        Ok(page)
+    }
+
+    /// Count the paths changed by a revision without collecting them.  Copy
+    /// source lines are part of their change rather than counted separately.
+    pub fn change_count<R: Into<Revision>>(&self, revision: R) -> Result<usize, SvnError> {
+        self.changed(revision)?
+            .try_fold(0, |count, change| change.map(|_| count + 1))
+    }
+
+    /// Call `f` with each change in a revision without collecting them,
+    /// stopping and killing svnlook early if it returns `ControlFlow::Break`.
+    ///
+    /// This is the memory-safe way to process very large revisions: each
+    /// change is dropped after `f` returns unless the caller keeps it.
+    pub fn for_each_change<R, F>(&self, revision: R, mut f: F) -> Result<(), SvnError>
+    where
+        R: Into<Revision>,
+        F: FnMut(SvnChange) -> ControlFlow<()>,
+    {
+        let mut changes = self.changed(revision)?;
+
+        for change in &mut changes {
+            if let ControlFlow::Break(()) = f(change?) {
+                changes.kill();
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// As `changed`, but also populate `SvnChange::exec_changed` by comparing
+    /// `svn:executable` before and after the revision.  This costs up to two
+    /// additional svnlook invocations per added, copied, or prop-changed file.
+    pub fn changed_with_exec<R: Into<Revision>>(
+        &self,
+        revision: R,
+    ) -> Result<SvnChangedIter, SvnError> {
+        let revision = revision.into();
+
+        Ok(self.changed(revision)?.with_exec(self.clone(), revision))
+    }
+
+    /// As `changed`, but paired with each file's size after the revision, or
+    /// `None` for directories and deleted paths.  Sizes are fetched by up to
+    /// `SIZE_PROBE_CONCURRENCY` concurrent `svnlook filesize` processes.
+    pub fn changed_with_sizes<R: Into<Revision>>(
+        &self,
+        revision: R,
+    ) -> Result<Vec<(SvnChange, Option<u64>)>, SvnError> {
+        let revision = revision.into();
+        let changes = self.changed(revision)?.collect_changes()?;
+        let mut sizes = Vec::with_capacity(changes.len());
+
+        for batch in changes.chunks(SIZE_PROBE_CONCURRENCY) {
+            let probes: Vec<_> = batch
+                .iter()
+                .map(|change| {
+                    if change.is_dir || change.status == SvnStatus::Deleted {
+                        return None;
+                    }
+
+                    let repository = self.clone();
+                    let path = change.path.clone();
+                    Some(thread::spawn(move || repository.filesize(revision, path)))
+                })
+                .collect();
+
+            for probe in probes {
+                sizes.push(match probe {
+                    Some(handle) => Some(
+                        handle
+                            .join()
+                            .unwrap_or_else(|e| std::panic::resume_unwind(e))?,
+                    ),
+                    None => None,
+                });
+            }
+        }
+
+        Ok(changes.into_iter().zip(sizes).collect())
+    }
+
+    /// List the directories changed by a revision.
+    pub fn dirs_changed<R: Into<Revision>>(&self, revision: R) -> Result<Vec<PathBuf>, SvnError> {
+        let n = self
+            .svnlook
+            .command()
+            .arg("dirs-changed")
+            .arg("-r")
+            .arg(revision.into().to_string())
+            .arg("--")
+            .arg(&self.path)
+            .output()?;
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        Ok(transaction::parse_dirs(&n.stdout[..]))
+    }
+
+    /// Inspect an uncommitted transaction, such as the one passed to a
+    /// pre-commit hook.
+    pub fn transaction<T: Into<String>>(&self, name: T) -> Transaction {
+        Transaction::new(self.clone(), name.into())
+    }
+
+    /// Stream a revision's changes, each bundled with the revision's author
+    /// and date so it can be published as a self-contained event.
+    pub fn change_events<R: Into<Revision>>(
+        &self,
+        revision: R,
+    ) -> Result<impl Iterator<Item = Result<ChangeEvent, SvnError>>, SvnError> {
+        let revision = revision.into();
+        let info = self.info(revision)?;
+        let author = info.author().map(String::from);
+
+        Ok(self.changed(revision)?.map(move |change| {
+            change.map(|change| ChangeEvent {
+                revision: info.revision,
+                author: author.clone(),
+                date: info.date,
+                change,
+            })
+        }))
+    }
+
+    /// Assemble a revision's metadata and changed paths into a compact record,
+    /// a structured subset of what `svnadmin dump` would emit.
+    pub fn revision_record<R: Into<Revision>>(
+        &self,
+        revision: R,
+    ) -> Result<RevisionRecord, SvnError> {
+        let revision = revision.into();
+
+        Ok(RevisionRecord::from(SvnRevisionSummary {
+            info: self.info(revision)?,
+            changes: self.changed(revision)?.collect_changes()?,
+        }))
+    }
+
+    /// A SHA-256 digest identifying a revision's content, for recognising
+    /// the same commit across mirrors.
+    ///
+    /// The digest covers the author, the date normalised to UTC, the message,
+    /// and each change's status label, path, and copy source, sorted by path.
+    /// The revision number itself is not included, so a commit replayed at a
+    /// different number still matches, but copy source revisions are.
+    #[cfg(feature = "fingerprint")]
+    pub fn revision_fingerprint<R: Into<Revision>>(
+        &self,
+        revision: R,
+    ) -> Result<[u8; 32], SvnError> {
+        use sha2::{Digest, Sha256};
+
+        let record = self.revision_record(revision)?;
+        let mut changes = record.changes;
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // Length-prefix each field so adjacent fields can't run together
+        let mut hasher = Sha256::new();
+        let mut field = |bytes: &[u8]| {
+            hasher.update((bytes.len() as u64).to_be_bytes());
+            hasher.update(bytes);
+        };
+
+        field(record.author.unwrap_or_default().as_bytes());
+        field(
+            record
+                .date
+                .with_timezone(&chrono::Utc)
+                .to_rfc3339()
+                .as_bytes(),
+        );
+        field(record.message.as_bytes());
+
+        for change in changes {
+            field(change.status.as_bytes());
+            field(change.path.to_string_lossy().as_bytes());
+            let from = change.copy_from.unwrap_or_default();
+            field(from.path.to_string_lossy().as_bytes());
+            field(from.revision.to_string().as_bytes());
+        }
+
+        Ok(hasher.finalize().into())
+    }
+
+    /// Compute fingerprints for each revision in the given range, running up
+    /// to `concurrency` revisions' `svnlook info` and `changed` at once.
+    /// Results are yielded in revision order.
+    #[cfg(feature = "fingerprint")]
+    pub fn fingerprint_range<R: RangeBounds<u64>>(
+        &self,
+        range: R,
+        concurrency: usize,
+    ) -> Result<SvnFingerprintIter, SvnError> {
+        Ok(SvnFingerprintIter::new(
+            self.clone(),
+            self.revision_range(range)?,
+            concurrency,
+        ))
+    }
+
+    /// Iterate over summaries of each revision in the given range.
+    ///
+    /// `youngest()` is only consulted if the range has no upper bound, so an
+    /// indexer can process `n..=m`, persist `m`, and later resume with
+    /// `walk(m + 1..)` or another bounded range.
+    pub fn walk<R: RangeBounds<u64>>(&self, range: R) -> Result<SvnWalk, SvnError> {
+        Ok(SvnWalk::new(self.clone(), self.revision_range(range)?))
+    }
+
+    /// Yield the metadata of each revision in `range` which changed `path` or
+    /// anything beneath it, oldest first.
+    ///
+    /// This runs `svnlook changed` once per revision in the range, stopping
+    /// each as soon as a match is found, and `svnlook info` only for matching
+    /// revisions.  Unlike `history`, copies are not followed.
+    pub fn path_log<P: Into<PathBuf>, R: RangeBounds<u64>>(
+        &self,
+        path: P,
+        range: R,
+    ) -> Result<SvnPathLog, SvnError> {
+        Ok(SvnPathLog::new(
+            self.clone(),
+            self.revision_range(range)?,
+            path.into(),
+        ))
+    }
+
+    /// Compute diffstats for each revision in the given range, running up to
+    /// `concurrency` svnlook diff processes at once.  Results are yielded in
+    /// revision order.
+    pub fn diffstats_range<R: RangeBounds<u64>>(
+        &self,
+        range: R,
+        concurrency: usize,
+    ) -> Result<SvnDiffstatIter, SvnError> {
+        Ok(SvnDiffstatIter::new(
+            self.clone(),
+            self.revision_range(range)?,
+            concurrency,
+        ))
+    }
+
+    fn revision_range<R: RangeBounds<u64>>(&self, range: R) -> Result<Range<u64>, SvnError> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.saturating_add(1),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.youngest()? + 1,
+        };
+
+        Ok(start..end)
+    }
+
     pub fn diff(&self) -> SvnDiffBuilder {
         SvnDiffBuilder::new(&self.path, self.svnlook.command())
     }
 
-    pub fn cat<R: AsRef<Path>>(
+    pub fn history(&self) -> SvnHistoryBuilder {
+        SvnHistoryBuilder::new(&self.path, self.svnlook.command())
+    }
+
+    /// The diff of a single file in each revision that changed it, oldest
+    /// first, following its history back through copies.
+    ///
+    /// This runs a full `svnlook diff` for each revision in the file's history.
+    pub fn file_diffs<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<impl Iterator<Item = Result<(u64, SvnFileDiff), SvnError>>, SvnError> {
+        let mut history = self
+            .history()
+            .path(path.as_ref())
+            .spawn()?
+            .collect_entries()?;
+        history.reverse();
+
+        let repository = self.clone();
+        Ok(history.into_iter().filter_map(move |entry| {
+            repository
+                .file_diff(entry.revision, &entry.path)
+                .map(|diff| diff.map(|diff| (entry.revision, diff)))
+                .transpose()
+        }))
+    }
+
+    /// The revision in which the node at `path` in revision `as_of` was
+    /// created.  For a path created by a copy, this is the revision of the
+    /// copy rather than that of the copy source.
+    ///
+    /// Returns `SvnError::PathNotFound` if svnlook cannot find the path, and
+    /// any other svnlook failure as-is.
+    pub fn added_in<P: AsRef<Path>, R: Into<Revision>>(
         &self,
-        revision: u64,
-        filename: R,
+        path: P,
+        as_of: R,
+    ) -> Result<u64, SvnError> {
+        let path = path.as_ref();
+        let relative = path.strip_prefix("/").unwrap_or(path);
+        let not_found = || SvnError::PathNotFound(path.to_path_buf());
+
+        let n = self.history().revision(as_of).path(path).output()?;
+
+        if !n.status.success() {
+            return Err(if reports_error(&n.stderr, "E160013") {
+                not_found()
+            } else {
+                SvnError::from(n.status)
+            });
+        }
+
+        let history = parse_history(&n.stdout)?;
+
+        // History runs youngest first, and follows copies back to their
+        // source paths, so stop at the first entry under another path.
+        history
+            .iter()
+            .take_while(|entry| entry.path == relative)
+            .last()
+            .map(|entry| entry.revision)
+            .ok_or_else(not_found)
+    }
+
+    /// List the files with a file header in a revision's diff, much like
+    /// `git diff --name-only`.  Unlike `changed`, files with only property
+    /// changes are excluded, and binary files are included.
+    pub fn diff_name_only<R: Into<Revision>>(&self, revision: R) -> Result<Vec<PathBuf>, SvnError> {
+        diff_file_paths(self.diff().revision(revision).spawn()?)
+    }
+
+    /// Find copies in a revision whose source was deleted in the same
+    /// revision, classifying each as a pure rename or one which was also
+    /// modified.  Files are compared by content and property changes; a
+    /// directory counts as modified if anything beneath it changed.
+    ///
+    /// Each renamed file costs two `svnlook cat` runs, streamed and compared
+    /// until the first difference.
+    pub fn renames<R: Into<Revision>>(&self, revision: R) -> Result<Vec<Rename>, SvnError> {
+        let revision = revision.into();
+        let changes = self.changed(revision)?.collect_changes()?;
+
+        let deleted: HashSet<&Path> = changes
+            .iter()
+            .filter(|change| change.status == SvnStatus::Deleted)
+            .map(|change| change.path.as_path())
+            .collect();
+
+        let mut renames = vec![];
+        for change in &changes {
+            let from = match &change.status {
+                SvnStatus::Copied(from) | SvnStatus::Replaced(Some(from))
+                    if deleted.contains(from.path.as_path()) =>
+                {
+                    from
+                }
+                _ => continue,
+            };
+
+            let modified = if change.is_dir {
+                changes
+                    .iter()
+                    .any(|other| other.path != change.path && other.path.starts_with(&change.path))
+            } else {
+                change.props_changed
+                    || !same_content(
+                        self.cat(from.revision, &from.path)?,
+                        self.cat(revision, &change.path)?,
+                    )?
+            };
+
+            renames.push(Rename {
+                from: from.clone(),
+                to: change.path.clone(),
+                is_dir: change.is_dir,
+                kind: if modified {
+                    RenameKind::Modified
+                } else {
+                    RenameKind::Pure
+                },
+            });
+        }
+
+        Ok(renames)
+    }
+
+    /// Extract the diff for a single file from a revision's diff, if present.
+    pub fn file_diff<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Option<SvnFileDiff>, SvnError> {
+        let path = path.as_ref();
+        let path = path.strip_prefix("/").unwrap_or(path);
+
+        for diff in self.diff().revision(revision).spawn_structured()? {
+            let diff = diff?;
+            if diff.path == path {
+                return Ok(Some(diff));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn tree(&self) -> SvnTreeBuilder {
+        SvnTreeBuilder::new(&self.path, self.svnlook.command())
+    }
+
+    /// List the paths beneath `path` which differ between the trees of two
+    /// revisions: those present in only one, and those present in both whose
+    /// node-revision IDs differ.  Directories containing changes have new IDs
+    /// and so are included.  Paths are returned sorted.
+    pub fn tree_diff<A: Into<Revision>, B: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        rev_a: A,
+        rev_b: B,
+        path: P,
+    ) -> Result<Vec<PathBuf>, SvnError> {
+        let path = path.as_ref();
+
+        let mut only_a = self
+            .tree()
+            .revision(rev_a)
+            .path(path)
+            .show_ids()
+            .spawn()?
+            .map(|entry| entry.map(|entry| (entry.path, entry.node_id)))
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        let mut differing = vec![];
+        for entry in self.tree().revision(rev_b).path(path).show_ids().spawn()? {
+            let entry = entry?;
+            match only_a.remove(&entry.path) {
+                Some(node_id) if node_id == entry.node_id => (),
+                _ => differing.push(entry.path),
+            }
+        }
+
+        differing.extend(only_a.into_keys());
+        differing.sort();
+        Ok(differing)
+    }
+
+    /// List the immediate children of a directory, such as `branches` or
+    /// `tags`, as full paths.
+    pub fn list_dir<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Vec<PathBuf>, SvnError> {
+        let path = path.as_ref();
+        let relative = path.strip_prefix("/").unwrap_or(path);
+
+        self.tree()
+            .revision(revision)
+            .path(path)
+            .non_recursive()
+            .spawn()?
+            .filter(|entry| entry.as_ref().map_or(true, |entry| entry.path != relative))
+            .map(|entry| entry.map(|entry| entry.path))
+            .collect()
+    }
+
+    /// List paths at a revision matching a glob pattern, in tree order.
+    ///
+    /// Paths are matched relative to the repository root, without a leading
+    /// or trailing slash; `*` matches across directory separators.
+    #[cfg(feature = "glob")]
+    pub fn glob<R: Into<Revision>>(
+        &self,
+        revision: R,
+        pattern: &str,
+    ) -> Result<Vec<PathBuf>, SvnError> {
+        let matcher = globset::Glob::new(pattern)?.compile_matcher();
+
+        self.tree()
+            .revision(revision)
+            .spawn()?
+            .filter(|entry| {
+                entry.as_ref().map_or(true, |entry| {
+                    !entry.path.as_os_str().is_empty() && matcher.is_match(&entry.path)
+                })
+            })
+            .map(|entry| entry.map(|entry| entry.path))
+            .collect()
+    }
+
+    pub fn cat<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        filename: P,
     ) -> Result<SvnlookCommand, SvnError> {
         let mut cmd = self.svnlook.command();
         cmd.arg("cat")
             .arg("-r")
-            .arg(revision.to_string())
+            .arg(revision.into().to_string())
             .arg("--")
             .arg(&self.path)
             .arg(filename.as_ref().as_os_str());
 
         SvnlookCommand::spawn(&mut cmd)
     }
+
+    /// Cat the newest version of a file, even if it has since been deleted,
+    /// along with the revision it was read from.  Returns `None` if the path
+    /// never existed.
+    ///
+    /// Revisions are probed youngest first with `svnlook history`, so a file
+    /// deleted long ago costs one svnlook run per revision searched, and a
+    /// path which never existed costs one per revision in the repository.
+    /// Any failure other than the path being missing stops the search.
+    pub fn cat_latest_existing<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<(u64, SvnlookCommand)>, SvnError> {
+        let path = path.as_ref();
+
+        for revision in (0..=self.youngest()?).rev() {
+            let n = self
+                .svnlook
+                .command()
+                .arg("history")
+                .arg("-r")
+                .arg(revision.to_string())
+                .arg("--limit=1")
+                .arg("--")
+                .arg(&self.path)
+                .arg(path)
+                .stdout(Stdio::null())
+                .output()?;
+
+            if n.status.success() {
+                return Ok(Some((revision, self.cat(revision, path)?)));
+            }
+
+            // Only a missing path means an older revision is worth trying
+            if !reports_error(&n.stderr, "E160013") {
+                return Err(SvnError::from(n.status));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// As `cat`, for callers who would rather program against `BufRead` than
+    /// name `SvnlookCommand`.
+    pub fn cat_bufread<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        filename: P,
+    ) -> Result<impl BufRead, SvnError> {
+        self.cat(revision, filename)
+    }
+
+    /// Like `cat`, but reads fail with `ErrorKind::TimedOut` if svnlook stalls
+    /// for longer than `idle` between chunks, or if the whole transfer takes
+    /// longer than `total`.  Large files on slow storage may legitimately take
+    /// a long time overall, so `total` is optional.
+    pub fn cat_with_timeout<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        filename: P,
+        idle: Duration,
+        total: Option<Duration>,
+    ) -> Result<SvnCatReader, SvnError> {
+        let mut cmd = self.svnlook.command();
+        cmd.arg("cat")
+            .arg("-r")
+            .arg(revision.into().to_string())
+            .arg("--")
+            .arg(&self.path)
+            .arg(filename.as_ref().as_os_str());
+
+        SvnCatReader::spawn(&mut cmd, idle, total)
+    }
+
+    /// Like `cat`, but transparently decompressing files whose `svn:mime-type`
+    /// marks them as gzip.  Other files are returned unchanged.
+    #[cfg(feature = "gzip")]
+    pub fn cat_decoded<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Box<dyn Read + Send>, SvnError> {
+        let revision = revision.into();
+        let path = path.as_ref();
+
+        let mime = self
+            .optional_prop(revision, "svn:mime-type", path)?
+            .unwrap_or_default();
+        let gzipped = matches!(
+            String::from_utf8_lossy(&mime).trim(),
+            "application/gzip" | "application/x-gzip"
+        );
+
+        let cat = self.cat(revision, path)?;
+
+        Ok(if gzipped {
+            Box::new(flate2::read::MultiGzDecoder::new(cat))
+        } else {
+            Box::new(cat)
+        })
+    }
+
+    /// Read the full contents of a file, consulting the cache if one is set.
+    ///
+    /// Success is decided by svnlook's exit status rather than the length of
+    /// its output, so an empty file yields an empty `Vec` while a missing
+    /// path yields `SvnError::ExitFailure`.
+    pub fn cat_to_vec<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Vec<u8>, SvnError> {
+        let revision = revision.into();
+        let path = path.as_ref();
+
+        #[cfg(feature = "cache")]
+        {
+            if let Some(content) = self
+                .cache
+                .as_ref()
+                .and_then(|cache| cache.get(revision.0, path.to_path_buf()))
+            {
+                return Ok(content);
+            }
+        }
+
+        let n = self
+            .svnlook
+            .command()
+            .arg("cat")
+            .arg("-r")
+            .arg(revision.to_string())
+            .arg("--")
+            .arg(&self.path)
+            .arg(path.as_os_str())
+            .stderr(Stdio::inherit())
+            .output()?;
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        let content = n.stdout;
+
+        #[cfg(feature = "cache")]
+        {
+            if let Some(cache) = &self.cache {
+                cache.put(revision.0, path.to_path_buf(), content.clone());
+            }
+        }
+
+        Ok(content)
+    }
+
+    /// The contents of a file before and after a revision, for side-by-side
+    /// display.  The old side is `None` for additions, and read from the copy
+    /// source for copies, including files beneath a directory copied in the
+    /// same revision; the new side is `None` for deletions.
+    pub fn file_versions<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<FileVersions, SvnError> {
+        let revision = revision.into();
+        let path = path.as_ref();
+        let relative = path.strip_prefix("/").unwrap_or(path);
+
+        // Only the path itself and its ancestors can affect its origin
+        let changes = self
+            .changed(revision)?
+            .filter(|change| {
+                change
+                    .as_ref()
+                    .map_or(true, |change| relative.starts_with(&change.path))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.versions_in(revision, path, &changes)
+    }
+
+    fn versions_in(
+        &self,
+        revision: Revision,
+        path: &Path,
+        changes: &[SvnChange],
+    ) -> Result<FileVersions, SvnError> {
+        let relative = path.strip_prefix("/").unwrap_or(path);
+        let change = changes.iter().find(|change| change.path == relative);
+
+        if change.is_some_and(|change| change.is_dir) {
+            return Err(SvnError::NotFile(path.to_path_buf()));
+        }
+
+        let status = change.map(|change| &change.status);
+        let origin = changes
+            .iter()
+            .collect::<CopyOrigins>()
+            .origin(relative, false);
+
+        let old = match (status, origin) {
+            (Some(SvnStatus::Added), _) => None,
+            (_, Some(from)) => Some(self.cat_to_vec(from.revision, &from.path)?),
+            _ if revision.0 == 0 => None,
+            _ => Some(self.cat_to_vec(revision.0 - 1, path)?),
+        };
+
+        let new = match status {
+            Some(SvnStatus::Deleted) => None,
+            _ => Some(self.cat_to_vec(revision, path)?),
+        };
+
+        Ok((old, new))
+    }
+
+    /// Read the full contents of a file with its `svn:keywords` expanded, as
+    /// a working copy would have them.  Values are taken from the revision
+    /// in which the file last changed at or before `revision`.
+    ///
+    /// Supported keywords, with their aliases, are `Rev` (`Revision`,
+    /// `LastChangedRevision`), `Author` (`LastChangedBy`), `Date`
+    /// (`LastChangedDate`, in UTC), `URL` (`HeadURL`) and `Id`.  As svnlook
+    /// has no notion of the repository's public URL, `URL` expands to a
+    /// `file://` URL under the repository path.  Fixed-width `$Keyword::$`
+    /// anchors are left untouched.
+    pub fn cat_expanded<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Vec<u8>, SvnError> {
+        let revision = revision.into();
+        let path = path.as_ref();
+        let content = self.cat_to_vec(revision, path)?;
+
+        let keywords = match self.optional_prop(revision, "svn:keywords", path)? {
+            Some(keywords) => keywords,
+            None => return Ok(content),
+        };
+        let last_changed = self
+            .history()
+            .revision(revision)
+            .path(path)
+            .limit(1)
+            .spawn()?
+            .collect_entries()?
+            .first()
+            .map(|entry| entry.revision)
+            .ok_or(SvnError::ParseError)?;
+
+        let url = format!(
+            "file://{}/{}",
+            self.path.display(),
+            path.strip_prefix("/").unwrap_or(path).display()
+        );
+
+        Ok(KeywordValues::new(&keywords, &self.info(last_changed)?, url).expand(&content))
+    }
+
+    /// Read `len` bytes of a file starting at `offset`, killing svnlook once
+    /// they have been read.  The result is shorter if the file ends first.
+    /// Unless svnlook is killed, its exit status is checked, so a missing
+    /// path is an error even when nothing would be returned.
+    ///
+    /// svnlook's output is not seekable, so the first `offset` bytes are still
+    /// read and discarded, making this O(offset + len) rather than O(len).
+    pub fn cat_range<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+        offset: u64,
+        len: u64,
+    ) -> Result<Vec<u8>, SvnError> {
+        let mut cat = self.cat(revision, path)?;
+        let mut range = vec![];
+
+        // Reaching EOF checks svnlook's exit status, so only stop it early
+        // once there is more to read.  Peeking past a full range also catches
+        // a failure which produced no output, such as a missing path.
+        let read = io::copy(&mut cat.by_ref().take(offset), &mut io::sink()).and_then(|skipped| {
+            if skipped == offset && cat.by_ref().take(len).read_to_end(&mut range)? as u64 == len {
+                cat.fill_buf()?;
+            }
+            Ok(())
+        });
+        let _ = cat.kill();
+        read?;
+
+        Ok(range)
+    }
+
+    /// Classify a file as text or binary, using its `svn:mime-type` if set,
+    /// and otherwise by inspecting the first few KB of its contents.  svnlook
+    /// is killed once enough has been read, so large files are cheap.
+    pub fn content_kind<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<ContentKind, SvnError> {
+        let revision = revision.into();
+        let path = path.as_ref();
+
+        if let Some(mime) = self.optional_prop(revision, "svn:mime-type", path)? {
+            return Ok(ContentKind::from_mime_type(&mime));
+        }
+
+        let mut cat = self.cat(revision, path)?;
+        let mut prefix = vec![];
+        let read = cat.by_ref().take(SNIFF_SIZE).read_to_end(&mut prefix);
+        let _ = cat.kill();
+        read?;
+
+        Ok(ContentKind::sniff(&prefix))
+    }
+
+    /// Check that applying each file's parsed diff to its contents before the
+    /// revision reproduces its contents after it.  Files without hunks, such
+    /// as binary files or those with only property changes, are skipped.
+    ///
+    /// This reads each changed file before and after the revision, and so
+    /// runs several svnlook processes for each.
+    pub fn verify_revision<R: Into<Revision>>(&self, revision: R) -> Result<bool, SvnError> {
+        let revision = revision.into();
+        let changes = self.changed(revision)?.collect_changes()?;
+
+        for diff in self
+            .diff()
+            .revision(revision)
+            .diff_copy_from()
+            .spawn_structured()?
+        {
+            let diff = diff?;
+            if diff.hunks.is_empty() {
+                continue;
+            }
+
+            let (old, new) = self.versions_in(revision, &diff.path, &changes)?;
+            if diff.apply(&old.unwrap_or_default()) != Some(new.unwrap_or_default()) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// The size in bytes of a file at the given revision.
+    pub fn filesize<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<u64, SvnError> {
+        let n = self
+            .svnlook
+            .command()
+            .arg("filesize")
+            .arg("-r")
+            .arg(revision.into().to_string())
+            .arg("--")
+            .arg(&self.path)
+            .arg(path.as_ref().as_os_str())
+            .output()?;
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        str::from_utf8(&n.stdout[..])?
+            .trim()
+            .parse()
+            .map_err(SvnError::from)
+    }
+
+    /// Read the full contents of a file, checking the number of bytes read
+    /// against the size svnlook reports for it.
+    ///
+    /// svnlook does not expose stored checksums, so this guards against
+    /// truncation rather than corruption.
+    pub fn cat_verified<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Vec<u8>, SvnError> {
+        let revision = revision.into();
+        let path = path.as_ref();
+
+        let expected = self.filesize(revision, path)?;
+        let mut content = vec![];
+        self.cat(revision, path)?.read_to_end(&mut content)?;
+
+        if content.len() as u64 != expected {
+            return Err(SvnError::SizeMismatch(expected, content.len() as u64));
+        }
+
+        Ok(content)
+    }
+
+    /// List the names of the properties set on a path.
+    pub fn proplist<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Vec<String>, SvnError> {
+        let n = self
+            .svnlook
+            .command()
+            .arg("proplist")
+            .arg("-r")
+            .arg(revision.into().to_string())
+            .arg("--")
+            .arg(&self.path)
+            .arg(path.as_ref().as_os_str())
+            .output()?;
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        Ok(str::from_utf8(&n.stdout[..])?
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    /// Fetch the value of a property on a path.  svnlook exits with an error
+    /// if the property is not set; use `optional_prop` if that matters.
+    pub fn propget<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        name: &str,
+        path: P,
+    ) -> Result<Vec<u8>, SvnError> {
+        let n = self
+            .svnlook
+            .command()
+            .arg("propget")
+            .arg("-r")
+            .arg(revision.into().to_string())
+            .arg("--")
+            .arg(&self.path)
+            .arg(name)
+            .arg(path.as_ref().as_os_str())
+            .output()?;
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        Ok(n.stdout)
+    }
+
+    /// Fetch the value of a property on a path, or `None` if it is not set.
+    /// Unlike checking `proplist` before `propget`, this runs svnlook once.
+    pub fn optional_prop<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        name: &str,
+        path: P,
+    ) -> Result<Option<Vec<u8>>, SvnError> {
+        let n = self
+            .svnlook
+            .command()
+            .arg("propget")
+            .arg("-r")
+            .arg(revision.into().to_string())
+            .arg("--")
+            .arg(&self.path)
+            .arg(name)
+            .arg(path.as_ref().as_os_str())
+            .output()?;
+
+        if n.status.success() {
+            Ok(Some(n.stdout))
+        } else if reports_error(&n.stderr, "E200017") {
+            Ok(None)
+        } else {
+            Err(SvnError::from(n.status))
+        }
+    }
+
+    /// Parse the `svn:mergeinfo` of a path, which is empty if it has none.
+    fn mergeinfo(&self, revision: Revision, path: &Path) -> Result<Mergeinfo, SvnError> {
+        match self.optional_prop(revision, "svn:mergeinfo", path)? {
+            Some(mergeinfo) => parse_mergeinfo(str::from_utf8(&mergeinfo)?),
+            None => Ok(Mergeinfo::new()),
+        }
+    }
+
+    /// Describe the revisions newly merged by a revision, by comparing the
+    /// `svn:mergeinfo` of each path with changed properties before and after
+    /// it.  Added and copied paths are skipped, as their mergeinfo comes from
+    /// the copy rather than a merge.
+    pub fn merge_summary<R: Into<Revision>>(
+        &self,
+        revision: R,
+    ) -> Result<Vec<MergeRecord>, SvnError> {
+        let revision = revision.into();
+        let mut records = vec![];
+
+        for change in self.changed(revision)? {
+            let change = change?;
+            match change.status {
+                SvnStatus::Updated | SvnStatus::PropChange if change.props_changed => (),
+                _ => continue,
+            }
+
+            let new = self.mergeinfo(revision, &change.path)?;
+            let old = match revision.0.checked_sub(1) {
+                Some(previous) => self.mergeinfo(Revision(previous), &change.path)?,
+                None => Mergeinfo::new(),
+            };
+
+            for (source, ranges) in new {
+                let old = old.get(&source).map_or(&[][..], Vec::as_slice);
+                let ranges = subtract_ranges(&ranges, old);
+
+                if !ranges.is_empty() {
+                    records.push(MergeRecord {
+                        target: change.path.clone(),
+                        source,
+                        ranges,
+                    });
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Fetch the value of a revision property.
+    pub fn revprop<R: Into<Revision>>(&self, revision: R, name: &str) -> Result<Vec<u8>, SvnError> {
+        let n = self
+            .svnlook
+            .command()
+            .arg("propget")
+            .arg("--revprop")
+            .arg("-r")
+            .arg(revision.into().to_string())
+            .arg("--")
+            .arg(&self.path)
+            .arg(name)
+            .output()?;
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        Ok(n.stdout)
+    }
+
+    /// Parse the `svn:date` revision property, which unlike the date reported
+    /// by `info` is ISO 8601 in UTC with microsecond precision.
+    pub fn date_revprop<R: Into<Revision>>(
+        &self,
+        revision: R,
+    ) -> Result<DateTime<FixedOffset>, SvnError> {
+        let date = self.revprop(revision, "svn:date")?;
+
+        DateTime::parse_from_rfc3339(str::from_utf8(&date[..])?.trim())
+            .map_err(|_| SvnError::ParseError)
+    }
+
+    /// Parse the `svn:externals` definitions set on a directory, returning an
+    /// empty list if there are none.
+    pub fn externals<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Vec<ExternalDef>, SvnError> {
+        let revision = revision.into();
+        let path = path.as_ref();
+
+        match self.optional_prop(revision, "svn:externals", path)? {
+            Some(externals) => parse_externals(str::from_utf8(&externals)?),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Return the target of a symlink, or `None` if the path is not one.
+    ///
+    /// Subversion stores symlinks as files with `svn:special` set, containing
+    /// `link TARGET`.
+    pub fn read_symlink<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<Option<PathBuf>, SvnError> {
+        let revision = revision.into();
+        let path = path.as_ref();
+
+        if self.optional_prop(revision, "svn:special", path)?.is_none() {
+            return Ok(None);
+        }
+
+        let mut content = vec![];
+        self.cat(revision, path)?.read_to_end(&mut content)?;
+
+        content
+            .strip_prefix(b"link ")
+            .map(|target| Some(PathBuf::from(String::from_utf8_lossy(target).to_string())))
+            .ok_or(SvnError::ParseError)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::testing::fake_repository;
+
+    #[test]
+    fn streaming_exit_failure() {
+        let repo = fake_repository("printf 'A   a\\n'\nexit 3\n");
+
+        match repo.changed(1).unwrap().collect_changes() {
+            Err(SvnError::ExitFailure(status)) => assert_eq!(status.code(), Some(3)),
+            other => panic!("expected ExitFailure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn streaming_signalled() {
+        let repo = fake_repository("kill -9 $$\n");
+
+        match repo.changed(1).unwrap().collect_changes() {
+            Err(SvnError::Signalled(9)) => (),
+            other => panic!("expected Signalled(9), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn added_in_missing_path() {
+        let repo = fake_repository(
+            "echo \"svnlook: E160013: Path '/trunk/nope' does not exist\" >&2\nexit 1\n",
+        );
+
+        match repo.added_in("/trunk/nope", 3) {
+            Err(SvnError::PathNotFound(path)) => assert_eq!(path, Path::new("/trunk/nope")),
+            other => panic!("expected PathNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn added_in_other_errors() {
+        let repo = fake_repository("echo 'svnlook: E000013: Permission denied' >&2\nexit 1\n");
+
+        match repo.added_in("/trunk/f", 3) {
+            Err(SvnError::ExitFailure(status)) => assert_eq!(status.code(), Some(1)),
+            other => panic!("expected ExitFailure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn added_in_stops_at_copy() {
+        let repo = fake_repository(
+            "printf 'REVISION   PATH\n--------   ----\n       7   /branches/x/f\n       5   /branches/x/f\n       3   /trunk/f\n'\n",
+        );
+
+        assert_eq!(repo.added_in("/branches/x/f", 7).unwrap(), 5);
+    }
+
+    const COPIED_DIR: &str = r#"case "$1" in
+changed) printf 'A + branches/x/\n    (from trunk/:r4)\nUU  branches/x/f\n' ;;
+cat) echo "$3:$6" ;;
+propget) case "$3:$7" in
+    4:trunk/f|5:branches/x/f) echo '*' ;;
+    *) echo "svnlook: E200017: Property '$6' not found on path '$7' in revision $3" >&2; exit 1 ;;
+    esac ;;
+esac
+"#;
+
+    #[test]
+    fn file_versions_beneath_copied_dir() {
+        let repo = fake_repository(COPIED_DIR);
+
+        let (old, new) = repo.file_versions(5, "branches/x/f").unwrap();
+        assert_eq!(old.unwrap(), b"4:trunk/f\n");
+        assert_eq!(new.unwrap(), b"5:branches/x/f\n");
+    }
+
+    #[test]
+    fn exec_unchanged_beneath_copied_dir() {
+        let repo = fake_repository(COPIED_DIR);
+
+        let changes = repo
+            .changed_with_exec(5)
+            .unwrap()
+            .collect_changes()
+            .unwrap();
+        assert_eq!(changes[1].path, Path::new("branches/x/f"));
+        assert_eq!(changes[1].exec_changed, None);
+    }
+
+    const RENAMES: &str = r#"case "$1" in
+changed) printf '%s\n' 'D   trunk/a' 'A + trunk/b' '    (from trunk/a:r4)' \
+    'D   trunk/c' 'A + trunk/d' '    (from trunk/c:r4)' \
+    'D   trunk/old/' 'A + trunk/new/' '    (from trunk/old/:r4)' 'U   trunk/new/f' \
+    'D   trunk/x/' 'A + trunk/y/' '    (from trunk/x/:r4)' \
+    'A + trunk/e' '    (from trunk/z:r4)' ;;
+cat) case "$3:$6" in
+    4:trunk/c) echo old ;;
+    5:trunk/d) echo new ;;
+    *) echo same ;;
+    esac ;;
+esac
+"#;
+
+    #[test]
+    fn renames() {
+        let repo = fake_repository(RENAMES);
+
+        let renames = repo
+            .renames(5)
+            .unwrap()
+            .into_iter()
+            .map(|rename| (rename.from.path, rename.to, rename.is_dir, rename.kind))
+            .collect::<Vec<_>>();
+        let rename = |from: &str, to: &str, is_dir, kind| {
+            (PathBuf::from(from), PathBuf::from(to), is_dir, kind)
+        };
+
+        assert_eq!(
+            renames,
+            [
+                rename("trunk/a", "trunk/b", false, RenameKind::Pure),
+                rename("trunk/c", "trunk/d", false, RenameKind::Modified),
+                rename("trunk/old", "trunk/new", true, RenameKind::Modified),
+                rename("trunk/x", "trunk/y", true, RenameKind::Pure),
+            ]
+        );
+    }
+
+    const CAT_RANGE: &str = r#"case "$6" in
+f) printf hello ;;
+*) echo "svnlook: E160013: Path '$6' does not exist" >&2; exit 1 ;;
+esac
+"#;
+
+    const CAT: &str = r#"case "$6" in
+empty) ;;
+*) echo "svnlook: E160013: Path '$6' does not exist" >&2; exit 1 ;;
+esac
+"#;
+
+    const LATEST: &str = r#"case "$1:$3" in
+youngest:*) echo 3 ;;
+history:3) echo "svnlook: E160013: Path 'f' does not exist" >&2; exit 1 ;;
+history:*) ;;
+cat:*) echo "$3" ;;
+esac
+"#;
+
+    #[test]
+    fn cat_latest_existing_skips_missing() {
+        let repo = fake_repository(LATEST);
+
+        let (revision, mut cat) = repo.cat_latest_existing("f").unwrap().unwrap();
+        let mut content = String::new();
+        cat.read_to_string(&mut content).unwrap();
+
+        assert_eq!(revision, 2);
+        assert_eq!(content, "2\n");
+    }
+
+    #[test]
+    fn cat_latest_existing_propagates_other_errors() {
+        let repo = fake_repository(&LATEST.replace("E160013", "E000013"));
+
+        match repo.cat_latest_existing("f") {
+            Err(SvnError::ExitFailure(status)) => assert_eq!(status.code(), Some(1)),
+            other => panic!(
+                "expected ExitFailure, got {:?}",
+                other.map(|o| o.map(|o| o.0))
+            ),
+        }
+    }
+
+    const PROPS: &str = r#"case "$1:$6:$7" in
+propget:svn:keywords:f) printf 'Rev Author' ;;
+propget:svn:keywords:broken) echo "svnlook: E000013: Permission denied" >&2; exit 1 ;;
+propget:*) echo "svnlook: E200017: Property '$6' not found on path '$7' in revision $3" >&2; exit 1 ;;
+cat:*) printf '$Rev$ by $Author$\n' ;;
+history:*) printf 'REVISION   PATH\n--------   ----\n       4   /f\n' ;;
+info:*) printf 'alice\n2020-01-02 03:04:05 +0000 (Thu, 02 Jan 2020)\n2\nhi\n' ;;
+esac
+"#;
+
+    #[test]
+    fn optional_prop() {
+        let repo = fake_repository(PROPS);
+
+        assert_eq!(
+            repo.optional_prop(5, "svn:keywords", "f").unwrap(),
+            Some(b"Rev Author".to_vec())
+        );
+        assert_eq!(repo.optional_prop(5, "svn:eol-style", "f").unwrap(), None);
+
+        match repo.optional_prop(5, "svn:keywords", "broken") {
+            Err(SvnError::ExitFailure(status)) => assert_eq!(status.code(), Some(1)),
+            other => panic!("expected ExitFailure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cat_expanded() {
+        let repo = fake_repository(PROPS);
+
+        assert_eq!(
+            repo.cat_expanded(5, "f").unwrap(),
+            b"$Rev: 4 $ by $Author: alice $\n"
+        );
+        assert_eq!(repo.cat_expanded(5, "g").unwrap(), b"$Rev$ by $Author$\n");
+    }
+
+    #[test]
+    fn cat_range() {
+        let repo = fake_repository(CAT_RANGE);
+
+        assert_eq!(repo.cat_range(1, "f", 1, 3).unwrap(), b"ell");
+        assert_eq!(repo.cat_range(1, "f", 3, 10).unwrap(), b"lo");
+        assert_eq!(repo.cat_range(1, "f", 10, 3).unwrap(), b"");
+        assert_eq!(repo.cat_range(1, "f", 0, 0).unwrap(), b"");
+    }
+
+    #[test]
+    fn cat_range_missing_path() {
+        let repo = fake_repository(CAT_RANGE);
+
+        for (offset, len) in &[(0, 0), (0, 3), (10, 3)] {
+            match repo.cat_range(1, "nope", *offset, *len) {
+                Err(SvnError::ExitFailure(status)) => assert_eq!(status.code(), Some(1)),
+                other => panic!("expected ExitFailure, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn cat_empty_file() {
+        let repo = fake_repository(CAT);
+        assert_eq!(repo.cat_to_vec(1, "empty").unwrap(), b"");
+    }
+
+    #[test]
+    fn cat_missing_path() {
+        let repo = fake_repository(CAT);
+
+        match repo.cat_to_vec(1, "missing") {
+            Err(SvnError::ExitFailure(status)) => assert_eq!(status.code(), Some(1)),
+            other => panic!("expected ExitFailure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn collect_diffs_kills_on_error() {
+        // svnlook stalls after a malformed hunk header, so only killing it
+        // avoids waiting for it to exit
+        let repo = fake_repository(
+            "printf 'Modified: f\\n===\\n--- f\\n+++ f\\n@@ bad @@\\n'\nsleep 10\n",
+        );
+        let start = std::time::Instant::now();
+
+        assert!(repo
+            .diff()
+            .spawn_structured()
+            .unwrap()
+            .collect_diffs()
+            .is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn changed_page_reports_skipped_errors() {
+        let repo = fake_repository("printf 'bogus\nA   a\nA   b\nA   c\n'\n");
+
+        match repo.changed_page(1, 1, 5) {
+            Err(SvnError::MalformedChange(line)) => assert_eq!(line, "bogus"),
+            other => panic!("expected MalformedChange, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn changed_page_bounds() {
+        let repo = fake_repository("printf 'A   a\nA   b\nA   c\n'\n");
+
+        let page = repo.changed_page(1, 1, 1).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].path, Path::new("b"));
+        assert!(repo.changed_page(1, 5, 1).unwrap().is_empty());
+    }
 }
@@ -1,21 +1,5 @@
 use std::env;
 
-fn worlds_crappiest_diffstat<R: std::io::BufRead>(diff: R) -> std::io::Result<(u32, u32)> {
-    let mut counts = (0, 0);
-    for line in diff.split(b'\n') {
-        match line?.first() {
-            Some(b'+') => {
-                counts.0 += 1;
-            }
-            Some(b'-') => {
-                counts.1 += 1;
-            }
-            _ => (),
-        }
-    }
-    Ok(counts)
-}
-
 fn main() -> Result<(), svnlook::SvnError> {
     let cmd = env::args().nth(1).expect("Need a command");
     let repo = svnlook::Repository::from(env::args_os().nth(2).expect("Need a repository path"));
@@ -23,14 +7,14 @@ fn main() -> Result<(), svnlook::SvnError> {
     match &cmd[..] {
         "youngest" => println!("{}", repo.youngest()?),
         "changes" => {
-            let rev = env::args()
+            let rev: u64 = env::args()
                 .nth(3)
                 .expect("Need a revision")
                 .parse()
                 .expect("Not a number");
             for change in repo.changed(rev)? {
                 let change = change?;
-                print!("   {:.8}: ", change.status);
+                print!("   {}: ", change.padded_label(4));
 
                 if let svnlook::SvnStatus::Copied(from) = change.status {
                     print!("{}@r{} -> ", from.path.display(), from.revision);
@@ -40,7 +24,7 @@ fn main() -> Result<(), svnlook::SvnError> {
             }
         }
         "diff" => {
-            let rev = env::args()
+            let rev: u64 = env::args()
                 .nth(3)
                 .expect("Need a revision")
                 .parse()
@@ -51,7 +35,7 @@ fn main() -> Result<(), svnlook::SvnError> {
             )?;
         }
         "cat" => {
-            let rev = env::args()
+            let rev: u64 = env::args()
                 .nth(3)
                 .expect("Need a revision")
                 .parse()
@@ -77,7 +61,7 @@ fn main() -> Result<(), svnlook::SvnError> {
                 );
                 for change in changed {
                     let change = change?;
-                    print!("   {:.8}: ", change.status);
+                    print!("   {}: ", change.padded_label(4));
 
                     if let svnlook::SvnStatus::Copied(from) = change.status {
                         print!("{}@r{} -> ", from.path.display(), from.revision);
@@ -86,8 +70,8 @@ fn main() -> Result<(), svnlook::SvnError> {
                     println!("{}", change.path.display());
                 }
 
-                let diff = worlds_crappiest_diffstat(diff)?;
-                println!("Delta: +{} -{}", diff.0, diff.1);
+                let diff = svnlook::Diffstat::from_reader(diff)?;
+                println!("Delta: +{} -{}", diff.added, diff.removed);
             }
         }
         _ => {
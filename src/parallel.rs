@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::SvnError;
+
+type RevisionFn<T> = Arc<dyn Fn(u64) -> Result<T, SvnError> + Send + Sync>;
+
+/// Runs a function for each revision in a range on up to `concurrency`
+/// threads at once, yielding results in revision order
+pub(crate) struct ParallelRevisions<T> {
+    f: RevisionFn<T>,
+    revisions: Range<u64>,
+    concurrency: usize,
+    pending: VecDeque<(u64, JoinHandle<Result<T, SvnError>>)>,
+}
+
+impl<T: Send + 'static> ParallelRevisions<T> {
+    pub(crate) fn new<F>(revisions: Range<u64>, concurrency: usize, f: F) -> Self
+    where
+        F: Fn(u64) -> Result<T, SvnError> + Send + Sync + 'static,
+    {
+        Self {
+            f: Arc::new(f),
+            revisions,
+            concurrency: concurrency.max(1),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ParallelRevisions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParallelRevisions")
+            .field("revisions", &self.revisions)
+            .field("concurrency", &self.concurrency)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+impl<T: Send + 'static> Iterator for ParallelRevisions<T> {
+    type Item = Result<(u64, T), SvnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.len() < self.concurrency {
+            let revision = match self.revisions.next() {
+                Some(revision) => revision,
+                None => break,
+            };
+
+            let f = Arc::clone(&self.f);
+            self.pending
+                .push_back((revision, thread::spawn(move || f(revision))));
+        }
+
+        self.pending.pop_front().map(|(revision, handle)| {
+            handle
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                .map(|value| (revision, value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn ordered_and_bounded() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let (r, p) = (Arc::clone(&running), Arc::clone(&peak));
+        let results = ParallelRevisions::new(0..20, 3, move |revision| {
+            let now = r.fetch_add(1, Ordering::SeqCst) + 1;
+            p.fetch_max(now, Ordering::SeqCst);
+            // Later revisions finish first
+            thread::sleep(Duration::from_millis(20 - revision));
+            r.fetch_sub(1, Ordering::SeqCst);
+
+            if revision == 7 {
+                Err(SvnError::ParseError)
+            } else {
+                Ok(revision * 2)
+            }
+        })
+        .collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 20);
+        for (revision, result) in (0..).zip(results) {
+            match result {
+                Err(SvnError::ParseError) => assert_eq!(revision, 7),
+                Ok(result) => assert_eq!(result, (revision, revision * 2)),
+                Err(e) => panic!("unexpected error {:?}", e),
+            }
+        }
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+}
@@ -0,0 +1,174 @@
+use std::fs;
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::SvnError;
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: String,
+    allow: bool,
+    dir_only: bool,
+}
+
+/// A set of forbidden path patterns, in the style of a `.gitignore` file
+///
+/// Each non-blank line not starting with `#` is a glob.  A pattern containing
+/// a slash, other than a trailing one, is anchored to the repository root;
+/// otherwise it matches at any depth.  A trailing slash only matches
+/// directories, and a leading `!` allows paths an earlier rule forbade.  As
+/// with `.gitignore`, the last matching rule wins, and nothing beneath a
+/// forbidden directory can be allowed again.
+#[derive(Debug, Clone)]
+pub struct PathPolicy {
+    set: GlobSet,
+    rules: Vec<Rule>,
+}
+
+impl PathPolicy {
+    /// Parse rules from the contents of a policy file.
+    pub fn from_rules(rules: &str) -> Result<Self, SvnError> {
+        let mut set = GlobSetBuilder::new();
+        let mut parsed = vec![];
+
+        for line in rules.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (glob, allow) = match line.strip_prefix('!') {
+                Some(glob) => (glob, true),
+                None => (line, false),
+            };
+            let (glob, dir_only) = match glob.strip_suffix('/') {
+                Some(glob) => (glob, true),
+                None => (glob, false),
+            };
+
+            let anchored = match glob.strip_prefix('/') {
+                Some(glob) => glob.to_string(),
+                None if glob.contains('/') => glob.to_string(),
+                None => format!("**/{}", glob),
+            };
+
+            set.add(
+                GlobBuilder::new(&anchored)
+                    .literal_separator(true)
+                    .build()?,
+            );
+            parsed.push(Rule {
+                pattern: line.to_string(),
+                allow,
+                dir_only,
+            });
+        }
+
+        Ok(Self {
+            set: set.build()?,
+            rules: parsed,
+        })
+    }
+
+    /// Read and parse a policy file, such as `.svnpolicy`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SvnError> {
+        Self::from_rules(&fs::read_to_string(path)?)
+    }
+
+    /// The last rule matching `path` itself, ignoring its ancestors.
+    fn matching_rule(&self, path: &Path, is_dir: bool) -> Option<&Rule> {
+        self.set
+            .matches(path)
+            .into_iter()
+            .rev()
+            .map(|index| &self.rules[index])
+            .find(|rule| is_dir || !rule.dir_only)
+    }
+
+    /// The rule forbidding `path`, relative to the repository root, if any.
+    /// Paths beneath a forbidden directory are forbidden by that directory's
+    /// rule.
+    pub fn forbids<P: AsRef<Path>>(&self, path: P, is_dir: bool) -> Option<&str> {
+        let path = path.as_ref();
+        let path = path.strip_prefix("/").unwrap_or(path);
+
+        let mut ancestors = path
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .collect::<Vec<_>>();
+        ancestors.reverse();
+
+        ancestors.iter().find_map(|ancestor| {
+            let rule = self.matching_rule(ancestor, is_dir || *ancestor != path)?;
+            Some(rule.pattern.as_str()).filter(|_| !rule.allow)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(rules: &str) -> PathPolicy {
+        PathPolicy::from_rules(rules).unwrap()
+    }
+
+    #[test]
+    fn unanchored_patterns_match_at_any_depth() {
+        let policy = policy("# keys\n\n*.key\n");
+
+        assert_eq!(policy.forbids("a.key", false), Some("*.key"));
+        assert_eq!(policy.forbids("trunk/secrets/a.key", false), Some("*.key"));
+        assert_eq!(policy.forbids("/trunk/a.key", false), Some("*.key"));
+        assert_eq!(policy.forbids("trunk/a.keys", false), None);
+    }
+
+    #[test]
+    fn anchored_patterns_match_from_the_root() {
+        let policy = policy("/build\ntrunk/*.tmp\n");
+
+        assert_eq!(policy.forbids("build", false), Some("/build"));
+        assert_eq!(policy.forbids("trunk/build", false), None);
+        assert_eq!(policy.forbids("trunk/a.tmp", false), Some("trunk/*.tmp"));
+        assert_eq!(policy.forbids("/trunk/a.tmp", false), Some("trunk/*.tmp"));
+        assert_eq!(policy.forbids("trunk/x/a.tmp", false), None);
+        assert_eq!(policy.forbids("branches/trunk/a.tmp", false), None);
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let allowed = policy("*.log\n!keep.log\n");
+        assert_eq!(allowed.forbids("a.log", false), Some("*.log"));
+        assert_eq!(allowed.forbids("logs/keep.log", false), None);
+
+        let forbidden = policy("!keep.log\n*.log\n");
+        assert_eq!(forbidden.forbids("logs/keep.log", false), Some("*.log"));
+    }
+
+    #[test]
+    fn directory_rules() {
+        let policy = policy("target/\n");
+
+        assert_eq!(policy.forbids("target", true), Some("target/"));
+        assert_eq!(policy.forbids("target", false), None);
+        assert_eq!(policy.forbids("target/x.rs", false), Some("target/"));
+        assert_eq!(policy.forbids("a/target/debug/x", false), Some("target/"));
+        assert_eq!(policy.forbids("targets/x.rs", false), None);
+    }
+
+    #[test]
+    fn forbidden_ancestors_take_precedence() {
+        let policy = policy("secret/\n!secret/ok.txt\n*.bak\n!/trunk/\n");
+
+        assert_eq!(policy.forbids("secret/ok.txt", false), Some("secret/"));
+        assert_eq!(policy.forbids("trunk/a.bak", false), Some("*.bak"));
+
+        let reallowed = PathPolicy::from_rules("vendor/\n!vendor/\n").unwrap();
+        assert_eq!(reallowed.forbids("vendor/lib.rs", false), None);
+    }
+
+    #[test]
+    fn invalid_glob() {
+        assert!(PathPolicy::from_rules("a[\n").is_err());
+    }
+}
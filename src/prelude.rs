@@ -0,0 +1,17 @@
+//! Convenient re-exports of commonly used types
+//!
+//! ```
+//! use svnlook::prelude::*;
+//! ```
+
+pub use std::io::{BufRead, Read};
+
+pub use crate::{
+    Diffstat, Repository, Revision, SvnChange, SvnChangedIter, SvnDiffBuilder, SvnDiffIter,
+    SvnDiffstatIter, SvnError, SvnFileDiff, SvnFrom, SvnHistoryIter, SvnInfo, SvnPathLog,
+    SvnRevisionSummary, SvnStatus, SvnTreeBuilder, SvnTreeEntry, SvnTreeIter, SvnWalk, Svnlook,
+    SvnlookCommand,
+};
+
+#[cfg(feature = "fingerprint")]
+pub use crate::SvnFingerprintIter;
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use crate::{
+    Repository, Revision, SvnChangedIter, SvnDiffBuilder, SvnError, SvnInfo, SvnlookCommand,
+};
+
+/// A repository pinned to a single revision, as returned by `Repository::at`
+#[derive(Debug, Clone)]
+pub struct RepositoryAt {
+    repository: Repository,
+    revision: Revision,
+}
+
+impl RepositoryAt {
+    pub(crate) fn new(repository: Repository, revision: Revision) -> Self {
+        Self {
+            repository,
+            revision,
+        }
+    }
+
+    pub fn revision(&self) -> Revision {
+        self.revision
+    }
+
+    pub fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    pub fn info(&self) -> Result<SvnInfo, SvnError> {
+        self.repository.info(self.revision)
+    }
+
+    pub fn changed(&self) -> Result<SvnChangedIter, SvnError> {
+        self.repository.changed(self.revision)
+    }
+
+    pub fn diff(&self) -> SvnDiffBuilder {
+        let mut diff = self.repository.diff();
+        diff.revision(self.revision);
+        diff
+    }
+
+    pub fn cat<P: AsRef<Path>>(&self, filename: P) -> Result<SvnlookCommand, SvnError> {
+        self.repository.cat(self.revision, filename)
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt;
+
+/// A Subversion revision number
+///
+/// Methods taking a revision accept anything `Into<Revision>`, so plain `u64`
+/// values continue to work while making it harder to transpose arguments.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Revision(pub u64);
+
+impl From<u64> for Revision {
+    fn from(revision: u64) -> Self {
+        Self(revision)
+    }
+}
+
+impl From<Revision> for u64 {
+    fn from(revision: Revision) -> Self {
+        revision.0
+    }
+}
+
+impl fmt::Display for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
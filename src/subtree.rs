@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Repository, Revision, SvnChangedIter, SvnError, SvnFileDiff, SvnInfo, SvnlookCommand};
+
+/// A view of a repository scoped to a subtree, as returned by
+/// `Repository::subtree`
+///
+/// Paths passed in are taken relative to the subtree root, and paths
+/// returned are made relative to it, with changes outside it filtered out.
+#[derive(Debug, Clone)]
+pub struct SubtreeRepository {
+    repository: Repository,
+    root: PathBuf,
+}
+
+impl SubtreeRepository {
+    pub(crate) fn new(repository: Repository, root: PathBuf) -> Self {
+        let root = match root.strip_prefix("/") {
+            Ok(root) => root.to_path_buf(),
+            Err(_) => root,
+        };
+
+        Self { repository, root }
+    }
+
+    /// The subtree root, relative to the repository root
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    fn full_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        let path = path.as_ref();
+        self.root.join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    /// Revision metadata is not scoped by path, so this is the same as
+    /// `Repository::info`.
+    pub fn info<R: Into<Revision>>(&self, revision: R) -> Result<SvnInfo, SvnError> {
+        self.repository.info(revision)
+    }
+
+    /// Changes within the subtree, with paths relative to its root.
+    pub fn changed<R: Into<Revision>>(&self, revision: R) -> Result<SvnChangedIter, SvnError> {
+        Ok(self
+            .repository
+            .changed(revision)?
+            .strip_prefix(self.root.clone()))
+    }
+
+    /// Structured diffs of files within the subtree, with paths relative to
+    /// its root.
+    pub fn diff<R: Into<Revision>>(
+        &self,
+        revision: R,
+    ) -> Result<impl Iterator<Item = Result<SvnFileDiff, SvnError>>, SvnError> {
+        let root = self.root.clone();
+
+        Ok(self
+            .repository
+            .diff()
+            .revision(revision)
+            .spawn_structured()?
+            .filter_map(move |diff| match diff {
+                Ok(mut diff) => {
+                    diff.path = diff.path.strip_prefix(&root).ok()?.to_path_buf();
+                    Some(Ok(diff))
+                }
+                Err(e) => Some(Err(e)),
+            }))
+    }
+
+    pub fn cat<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<SvnlookCommand, SvnError> {
+        self.repository.cat(revision, self.full_path(path))
+    }
+
+    pub fn filesize<R: Into<Revision>, P: AsRef<Path>>(
+        &self,
+        revision: R,
+        path: P,
+    ) -> Result<u64, SvnError> {
+        self.repository.filesize(revision, self.full_path(path))
+    }
+}
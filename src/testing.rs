@@ -0,0 +1,19 @@
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{Repository, Svnlook};
+
+/// A repository whose svnlook is the given shell script.  It is run via `sh`,
+/// so it never needs to be executable.
+pub(crate) fn fake_repository(script: &str) -> Repository {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let path = std::env::temp_dir().join(format!(
+        "svnlook-test-{}-{}.sh",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::SeqCst)
+    ));
+    fs::write(&path, script).unwrap();
+
+    Svnlook::from(path).with_prefix(["sh"]).repository("/repo")
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::{Repository, Revision, SvnError, SvnInfo};
+
+/// An uncommitted transaction within a repository, as seen by pre-commit hooks
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    repository: Repository,
+    name: String,
+}
+
+pub(crate) fn parse_dirs(output: &[u8]) -> Vec<PathBuf> {
+    output
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| match line {
+            b"/" => line,
+            _ => line.strip_suffix(b"/").unwrap_or(line),
+        })
+        .map(|line| PathBuf::from(String::from_utf8_lossy(line).to_string()))
+        .collect()
+}
+
+impl Transaction {
+    pub(crate) fn new(repository: Repository, name: String) -> Self {
+        Self { repository, name }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    fn command(&self, subcommand: &str) -> Command {
+        let mut cmd = self.repository.svnlook.command();
+        cmd.arg(subcommand).arg("-t").arg(&self.name);
+        cmd
+    }
+
+    /// List the directories whose contents or properties the transaction
+    /// changes, which is cheaper than a full `changed` for directory-level
+    /// policy checks.
+    pub fn dirs_changed(&self) -> Result<Vec<PathBuf>, SvnError> {
+        let n = self
+            .command("dirs-changed")
+            .arg("--")
+            .arg(&self.repository.path)
+            .output()?;
+
+        if !n.status.success() {
+            return Err(SvnError::from(n.status));
+        }
+
+        Ok(parse_dirs(&n.stdout[..]))
+    }
+
+    /// The revision the transaction was created against.  Subversion names
+    /// transactions `<base>-<sequence>`, so this is read from the name rather
+    /// than the current youngest revision, which may have moved on.
+    pub fn base_revision(&self) -> Result<Revision, SvnError> {
+        let base = self.name.split('-').next().unwrap_or_default();
+        Ok(Revision(base.parse()?))
+    }
+
+    /// Metadata for the revision the transaction is being committed against.
+    pub fn base_info(&self) -> Result<SvnInfo, SvnError> {
+        self.repository.info(self.base_revision()?)
+    }
+}